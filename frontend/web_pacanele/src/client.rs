@@ -1,9 +1,9 @@
 use dioxus::prelude::*;
 use dioxus_logger::tracing::*;
 use pacanele2_client::{Keypair, Signer};
-use rules::Fruit;
+use rules::rule_set::SpinOutcome;
 
-use crate::wallet::{wallet_signals, BetAmountControl, CurrentWalletDropdown};
+use crate::wallet::{wallet_signals, BetAmountControl, CurrentWalletDropdown, RuleSetControl};
 
 #[component]
 pub fn SolanaDemo() -> Element {
@@ -18,12 +18,13 @@ pub fn SolanaDemo() -> Element {
             }
             CurrentWalletDropdown {}
             BetAmountControl {}
+            RuleSetControl {}
 
             button {
                 onclick: move |_| {
                     async move {
                         if let Some(k) = wallet.current_keypair.peek().as_ref() {
-                            let xr = get_spin_result_from_solana(k.insecure_clone(), *wallet.current_bet_exp.peek()).await;
+                            let xr = get_spin_result_from_solana(k.insecure_clone(), *wallet.current_bet_exp.peek(), *wallet.current_rule_set.peek()).await;
                             wallet.do_refresh_values.call(());
                             let xr = format!("{:#?}", xr);
                             output.set(xr);
@@ -47,14 +48,13 @@ pub fn SolanaDemo() -> Element {
 pub async fn get_spin_result_from_solana(
     sender: Keypair,
     bet_amount_exp: u8,
-) -> Result<((Vec<Fruit>, u16), Vec<String>), String> {
-    // return Ok(((vec![Fruit::seven;3], 12345), vec!["fake".to_string()]));
+    rule_set: u8,
+) -> Result<(SpinOutcome, Vec<String>), String> {
 
     let client = pacanele2_client::get_client().await;
-    use rules::Fruit;
-    let x = pacanele2_client::spin_pcnl(&client, sender, bet_amount_exp).await?;
+    let x = pacanele2_client::spin_pcnl(&client, sender, bet_amount_exp, rule_set).await?;
 
     let b = pacanele2_client::base64_decode_return(&x)?;
-    let xr = bincode::deserialize::<(Vec<Fruit>, u16)>(&b).map_err(|e| format!("{:?}", e))?;
+    let xr = bincode::deserialize::<SpinOutcome>(&b).map_err(|e| format!("{:?}", e))?;
     Ok((xr, x.log_messages.clone().unwrap()))
 }
@@ -8,11 +8,14 @@ use crate::{
     random::get_wheel_shuffle,
     state::{PcnlState, PcnlWheelState, ShuffleState, WheelShuffleState, WheelStage},
     time::{get_current_ts, sleep},
-    wallet::{wallet_signals, BetAmountControl, CurrentWalletDropdown},
+    wallet::{wallet_signals, BetAmountControl, CurrentWalletDropdown, RuleSetControl},
 };
 use dioxus::prelude::*;
 use dioxus_logger::tracing::info;
-use rules::{rule_set::RuleSet, Fruit};
+use rules::{
+    rule_set::{reward_multiple, AppliedCap, REWARD_ONE},
+    Fruit,
+};
 
 fn random_spin_period(on_autoplay: bool) -> f64 {
     let mut r = rand::thread_rng();
@@ -26,49 +29,55 @@ fn random_spin_period(on_autoplay: bool) -> f64 {
 
 #[component]
 pub fn Pacanele() -> Element {
-    let pcnl_count: u32 = 3;
+    let wallet = wallet_signals();
+    // the house can switch to a table with another number of wheels
+    let pcnl_count = use_memo(move || wallet.current_rules.read().wheel_count() as u32);
 
     let mut pcnl_state = use_signal(|| None);
     let mut shuf_state = use_signal(|| None);
     let enable_autoplay = use_signal(|| false);
-    let _init_state = use_resource(move || async move {
-        let mut v = vec![];
-        let mut v2 = vec![];
-        for i in 0..pcnl_count {
-            let shuffle = get_wheel_shuffle(i, pcnl_count);
-            let shuf_idx = shuffle
-                .iter()
-                .enumerate()
-                .map(|(i, x)| (*x, i as u32))
-                .collect::<HashMap<Fruit, u32>>();
-
-            let init_fruit = Fruit::all()[0];
-            let init_idx = shuf_idx[&init_fruit];
-            v2.push(WheelShuffleState {
-                pcnl_id: i,
-                shuffle,
-                idx: shuf_idx,
-            });
-
-            v.push(PcnlWheelState {
-                pcnl_id: i,
-                pcnl_count,
-                new_fruit: init_fruit.clone(),
-                old_fruit: init_fruit.clone(),
-                spin_count: 0,
-                new_idx: init_idx,
-                old_idx: init_idx,
-                spin_period: random_spin_period(*enable_autoplay.peek()),
-                wheel_stage: WheelStage::Ready,
-                rotations_diff: 0.0,
-            });
+    let _init_state = use_resource(move || {
+        // rebuild the wheels when their number changes
+        let pcnl_count = pcnl_count();
+        async move {
+            let mut v = vec![];
+            let mut v2 = vec![];
+            for i in 0..pcnl_count {
+                let shuffle = get_wheel_shuffle(i, pcnl_count);
+                let shuf_idx = shuffle
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| (*x, i as u32))
+                    .collect::<HashMap<Fruit, u32>>();
+
+                let init_fruit = Fruit::all()[0];
+                let init_idx = shuf_idx[&init_fruit];
+                v2.push(WheelShuffleState {
+                    pcnl_id: i,
+                    shuffle,
+                    idx: shuf_idx,
+                });
+
+                v.push(PcnlWheelState {
+                    pcnl_id: i,
+                    pcnl_count,
+                    new_fruit: init_fruit.clone(),
+                    old_fruit: init_fruit.clone(),
+                    spin_count: 0,
+                    new_idx: init_idx,
+                    old_idx: init_idx,
+                    spin_period: random_spin_period(*enable_autoplay.peek()),
+                    wheel_stage: WheelStage::Ready,
+                    rotations_diff: 0.0,
+                });
+            }
+            pcnl_state.set(Some(PcnlState {
+                wheels: v,
+                last_outcome: None,
+                last_messages: vec![],
+            }));
+            shuf_state.set(Some(ShuffleState { wheels: v2 }));
         }
-        pcnl_state.set(Some(PcnlState {
-            wheels: v,
-            last_win: None,
-            last_messages: vec![],
-        }));
-        shuf_state.set(Some(ShuffleState { wheels: v2 }));
     });
 
     rsx! {
@@ -77,6 +86,7 @@ pub fn Pacanele() -> Element {
             Win {pcnl_state}
         }
         div { id: "left-box" ,
+            DisplayJackpot {}
             DisplayCredit {}
             DisplayWinCombo {}
         }
@@ -86,14 +96,15 @@ pub fn Pacanele() -> Element {
             CurrentWalletDropdown{}
         }
         div { id: "right-box",
-            SpinButton { pcnl_state, shuf_state, pcnl_count, enable_autoplay }
+            SpinButton { pcnl_state, shuf_state, enable_autoplay }
             
             BetAmountControl {}
+            RuleSetControl {}
         }
 
         div { id: "pacanele",
             div { id: "x777",
-                SlotWheelRow { pcnl_state, shuf_state, pcnl_count }
+                SlotWheelRow { pcnl_state, shuf_state, pcnl_count: pcnl_count() }
 
             }
         }
@@ -112,15 +123,41 @@ fn DisplayCredit() -> Element {
     }
 }
 
+#[component]
+fn DisplayJackpot() -> Element {
+    let wallet = wallet_signals();
+    let jackpot = use_resource(move || {
+        // re-read the pool after every spin
+        let _sol = *wallet.current_sol.read();
+        async move {
+            let client = pacanele2_client::get_client().await;
+            pacanele2_client::get_jackpot_lamports(&client).await
+        }
+    });
+    let jackpot_sol = if let Some(Ok(lamports)) = jackpot.read().as_ref() {
+        *lamports as f64 / 1000000000.0
+    } else {
+        0.0
+    };
+
+    rsx! {
+        h1 {
+            style: "font-size: 300%; color: gold;",
+            "jackpot: {jackpot_sol} SOL"
+        }
+    }
+}
+
 #[component]
 fn DisplayWinCombo() -> Element {
-    let r = RuleSet::default_internal_deserialize().rewards();
+    let wallet = wallet_signals();
+    let r = wallet.current_rules.read().rewards();
     let mut r = r
         .iter()
         .filter(|x| *x.1 > 0)
-        .map(|x| ((x.0 .0.to_link_str(), x.0 .1), *x.1))
+        .map(|x| ((x.0 .0, x.0 .1), *x.1))
         .collect::<Vec<_>>();
-    r.sort_by_key(|a| -(a.1 as i32) - a.0 .1 as i32);
+    r.sort_by_key(|a| -(a.1 as i64) - a.0 .1 as i64);
 
     rsx! {
         div {
@@ -132,17 +169,18 @@ fn DisplayWinCombo() -> Element {
     }
 }
 #[component]
-fn DisplayWinSingleCombo(fruit: String, count: u8, reward: u16) -> Element {
+fn DisplayWinSingleCombo(fruit: Fruit, count: u8, reward: u32) -> Element {
     rsx! {
         div {
             class: "display-win-combo-single",
             for _i in 0..count {
                 img {
                     class: "combo-image",
-                    src: "/assets/img2/fruit/{fruit}.png",
+                    src: "/assets/{fruit.asset_path()}",
+                    alt: fruit.display_name(),
                 }
             }
-            {format!("{reward}")}
+            {format!("{}", reward_multiple(reward))}
         }
     }
 }
@@ -178,19 +216,70 @@ pub fn Autoplay(enable_autoplay: Signal<bool>) -> Element {
 
 #[component]
 fn Win(pcnl_state: Signal<Option<PcnlState>>) -> Element {
-    let win_box = if let Some(r) = pcnl_state.read().as_ref() {
-        if let Some(w) = r.last_win {
+    let outcome = pcnl_state
+        .read()
+        .as_ref()
+        .and_then(|r| r.last_outcome.clone());
+
+    let win_box = match outcome.as_ref() {
+        Some(o) if o.reward > 0 => {
+            let multiplier = if o.multiplier > 1 {
+                format!("free spin x{}", o.multiplier)
+            } else {
+                String::new()
+            };
             rsx! {
                 h1 {
                     style:"font-size:400%;color:red;",
-                    "Win: " {format!("{}", w)}
+                    "Win: " {format!("{}", reward_multiple(o.reward))}
+                }
+                div {
+                    class: "display-win-combo",
+                    for m in o.matched.iter() {
+                        DisplayWinSingleCombo {
+                            fruit: m.fruit,
+                            count: m.count,
+                            reward: m.reward,
+                        }
+                    }
+                }
+                h3 {"{multiplier}"}
+                for cap in o.caps.iter() {
+                    h3 {
+                        style:"color:orange;",
+                        {match cap {
+                            AppliedCap::MaxWin { uncapped, cap } => format!(
+                                "max win: {} capped to {}",
+                                *uncapped as f64 / REWARD_ONE as f64,
+                                reward_multiple(*cap)
+                            ),
+                            AppliedCap::Bank { win, paid } => format!("bank limit: {win} lamports capped to {paid}"),
+                        }}
+                    }
                 }
             }
-        } else {
-            rsx! {}
         }
-    } else {
-        rsx! {}
+        _ => rsx! {},
+    };
+
+    let jackpot_box = match outcome.as_ref() {
+        Some(o) if o.jackpot => rsx! {
+            h1 {
+                style:"font-size:400%;color:gold;",
+                "JACKPOT!"
+            }
+        },
+        _ => rsx! {},
+    };
+
+    let free_spins_box = match outcome.as_ref() {
+        Some(o) if o.free_spins > 0 => rsx! {
+            h1 {
+                style:"font-size:300%;color:blue;",
+                "Free spins: +" {format!("{}", o.free_spins)}
+            }
+        },
+        _ => rsx! {},
     };
 
     let msg_box = if let Some(r) = pcnl_state.read().as_ref() {
@@ -205,7 +294,9 @@ fn Win(pcnl_state: Signal<Option<PcnlState>>) -> Element {
             style: "display: flex",
             div {
                 style:"border: 1px solid red; width: 50cqw; height: 100cqh;",
+                {jackpot_box}
                 {win_box}
+                {free_spins_box}
             }
             div {
                 style:"border: 1px solid red; width: 50cqw; height: 100cqh;",
@@ -220,7 +311,6 @@ fn Win(pcnl_state: Signal<Option<PcnlState>>) -> Element {
 fn SpinButton(
     pcnl_state: Signal<Option<PcnlState>>,
     shuf_state: Signal<Option<ShuffleState>>,
-    pcnl_count: u32,
     enable_autoplay: ReadOnlySignal<bool>,
 ) -> Element {
     let wallet: crate::wallet::WalletSignals = wallet_signals();
@@ -269,7 +359,7 @@ fn SpinButton(
                     continue;
                 }
 
-                state.last_win = None;
+                state.last_outcome = None;
                 state.last_messages = vec![];
                 let state_init = state.clone();
                 // Start spin. we do not yet have spin results (can take 5-10s on chain),
@@ -304,20 +394,50 @@ fn SpinButton(
                     continue;
                 };
 
-                let res = get_spin_result_from_solana(keypair, *wallet.current_bet_exp.peek()).await;
+                let res = get_spin_result_from_solana(
+                    keypair,
+                    *wallet.current_bet_exp.peek(),
+                    *wallet.current_rule_set.peek(),
+                )
+                .await;
 
-                let (new_results, new_reward, log_messages) = match res {
-                    Ok(((new_results, new_reward), log_messages))  => (new_results, new_reward, log_messages),
+                let (outcome, log_messages) = match res {
+                    Ok(x) => x,
                     Err(e) => {
                         info!("PCNL FAIL : {:?}!!!", e);
                         effects_running.set(false);
                         send_audio_event(AudioEvent::StopAudio);
+                        // "bank too poor for this bet", "bet too small", ... from the program
+                        let mut state_init = state_init;
+                        state_init.last_messages = vec![e];
                         pcnl_state.set(Some(state_init));
                         continue;
                     }
                 };
                 wallet.do_refresh_values.call(());
-                assert!(new_results.len() == state.wheels.len());
+                if outcome.symbols.len() != state.wheels.len() {
+                    // the spin played a table with another number of wheels, the refresh
+                    // above reloads it and the wheels get rebuilt
+                    info!(
+                        "PCNL: {} symbols for {} wheels",
+                        outcome.symbols.len(),
+                        state.wheels.len()
+                    );
+                    effects_running.set(false);
+                    send_audio_event(AudioEvent::StopAudio);
+                    if let Some(x) = pcnl_state.write().as_mut() {
+                        x.last_messages = log_messages;
+                        x.last_messages
+                            .push(format!("the table changed, you got {:?}", outcome.symbols));
+                        for w in x.wheels.iter_mut() {
+                            w.wheel_stage = WheelStage::Ready;
+                        }
+                    }
+                    continue;
+                }
+                let pcnl_count = state.wheels.len() as u32;
+                // one win sound per bet won, a fraction of a bet still gets one
+                let new_reward = outcome.reward.div_ceil(REWARD_ONE).min(77) as u16;
                 send_audio_event(AudioEvent::HaveResults);
                 if let Some(x) = pcnl_state.write().as_mut() {
                     x.last_messages = log_messages;
@@ -325,15 +445,20 @@ fn SpinButton(
 
                 // now that we have the results, we can diverge into each wheel
                 let mut _fut = vec![];
-                for seq in compute_wheel_sequences(&state, &_shuf, new_results, spin_time) {
+                for seq in compute_wheel_sequences(&state, &_shuf, outcome.symbols.clone(), spin_time) {
                     _fut.push(spawn(async move {
                         sleep(seq.first_wait).await;
                         if let Some(x) = pcnl_state.write().as_mut() {
-                            x.wheels[seq.pcnl_id as usize] = seq.first_val;
+                            // the wheels may have been rebuilt for another table meanwhile
+                            if let Some(w) = x.wheels.get_mut(seq.pcnl_id as usize) {
+                                *w = seq.first_val;
+                            }
                         }
                         sleep(seq.second_wait).await;
                         if let Some(x) = pcnl_state.write().as_mut() {
-                            x.wheels[seq.pcnl_id as usize] = seq.second_val;
+                            if let Some(w) = x.wheels.get_mut(seq.pcnl_id as usize) {
+                                *w = seq.second_val;
+                            }
                         }
                         send_audio_event(AudioEvent::WheelStop {
                             wheel_id: seq.pcnl_id,
@@ -350,11 +475,7 @@ fn SpinButton(
                     sleep(0.15).await;
                     // send_audio_event(AudioEvent::WheelsFinished);
                     if let Some(x) = pcnl_state.write().as_mut() {
-                        x.last_win = if new_reward > 0 {
-                            Some(new_reward)
-                        } else {
-                            None
-                        };
+                        x.last_outcome = Some(outcome);
                     }
                     sleep(0.15).await;
                     if new_reward > 0 {
@@ -522,11 +643,17 @@ fn SlotWheelX(
     use_effect(move || {
         // let have_result = *have_result.read();
         if let (Some(data), Some(shuf)) = (pcnl_state.read().as_ref(), shuf_state.read().as_ref()) {
-            let _state = data.wheels[pcnl_id as usize].clone();
-            let shuf = shuf.wheels[pcnl_id as usize].clone();
-            assert_eq!(_state.pcnl_id, shuf.pcnl_id);
-
-            state.set(Some((_state, shuf)));
+            // the wheels can be rebuilt for another table after this one is gone
+            match (
+                data.wheels.get(pcnl_id as usize),
+                shuf.wheels.get(pcnl_id as usize),
+            ) {
+                (Some(_state), Some(shuf)) => {
+                    assert_eq!(_state.pcnl_id, shuf.pcnl_id);
+                    state.set(Some((_state.clone(), shuf.clone())));
+                }
+                _ => state.set(None),
+            }
         } else {
             state.set(None);
         }
@@ -551,7 +678,7 @@ fn SlotWheelInner(state: ReadOnlySignal<Option<(PcnlWheelState, WheelShuffleStat
         rsx! {
             for (i , fruct) in shuffle.shuffle.iter().enumerate() {
                 SlotImage {
-                    pic_name: fruct.to_link_str(),
+                    fruit: *fruct,
                     pic_pos: i as u32,
                     pic_count: shuffle.shuffle.len() as u32,
                     state: state.clone(),
@@ -564,7 +691,7 @@ fn SlotWheelInner(state: ReadOnlySignal<Option<(PcnlWheelState, WheelShuffleStat
 }
 
 #[component]
-fn SlotImage(pic_name: String, pic_pos: u32, pic_count: u32, state: PcnlWheelState) -> Element {
+fn SlotImage(fruit: Fruit, pic_pos: u32, pic_count: u32, state: PcnlWheelState) -> Element {
     let pic_pos_old = (pic_count + pic_pos - state.old_idx) % pic_count;
     let pic_pos_new = (pic_count + pic_pos - state.new_idx) % pic_count;
     let slot_diff = (pic_count + pic_pos_new - pic_pos_old) % pic_count;
@@ -592,7 +719,8 @@ fn SlotImage(pic_name: String, pic_pos: u32, pic_count: u32, state: PcnlWheelSta
         img {
             class: "fruit-image",
             style: "{animation} {final_transform}",
-            src: "/assets/img2/fruit/{pic_name}.png",
+            src: "/assets/{fruit.asset_path()}",
+            alt: fruit.display_name(),
         }
     }
 }
@@ -1,10 +1,12 @@
+use rules::rule_set::SpinOutcome;
 use rules::Fruit;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct PcnlState {
     pub wheels: Vec<PcnlWheelState>,
-    pub last_win: Option<u16>,
+    /// what the program returned for the last spin, once the wheels stopped
+    pub last_outcome: Option<SpinOutcome>,
     pub last_messages: Vec<String>,
 }
 
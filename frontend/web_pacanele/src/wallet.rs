@@ -6,6 +6,8 @@ use pacanele2_client::Account;
 use pacanele2_client::FromStr;
 use pacanele2_client::Keypair;
 use pacanele2_client::Pubkey;
+use pacanele2_client::RuleSet;
+use pacanele2_client::RuleSetId;
 use pacanele2_client::Signer;
 
 #[derive(Clone, Debug, Copy)]
@@ -22,6 +24,11 @@ pub struct WalletSignals {
     pub bet_exp_interval: Signal<Option<(u8, u8)>>,
     pub current_bet_exp: Signal<u8>,
     pub set_bet_exp: Callback<u8>,
+    /// table the next paid spin plays, the house picks it in its config: a `RuleSetId`
+    /// or `Config::ACCOUNT_RULE_SET`
+    pub current_rule_set: Signal<u8>,
+    /// the rules of `current_rule_set`
+    pub current_rules: Signal<RuleSet>,
 }
 
 pub fn init_make_wallet_selector() {
@@ -95,6 +102,23 @@ pub fn init_make_wallet_selector() {
         }
     });
 
+    let table_res = use_resource(move || {
+        // re-read the table after every spin, the house may have changed it
+        let _sol = *current_sol.read();
+        async move {
+            let client = pacanele2_client::get_client().await;
+            pacanele2_client::get_table(&client).await
+        }
+    });
+    let mut current_rule_set = use_signal(|| RuleSetId::DEFAULT as u8);
+    let mut current_rules = use_signal(|| RuleSetId::DEFAULT.rule_set());
+    use_effect(move || {
+        if let Some(Ok((id, rules))) = table_res.read().as_ref() {
+            current_rule_set.set(*id);
+            current_rules.set(rules.clone());
+        }
+    });
+
     let bet_interval_res = use_resource(move || {
         // re-run bet interval when account changes
         let key = current_wallet.read().as_ref().cloned();
@@ -176,7 +200,9 @@ pub fn init_make_wallet_selector() {
         all_wallets_pk,
         wallet_balance,
         set_current_wallet,
-        do_refresh_values,bet_exp_interval,current_bet_exp, set_bet_exp
+        do_refresh_values,bet_exp_interval,current_bet_exp, set_bet_exp,
+        current_rule_set,
+        current_rules,
     });
 }
 
@@ -228,6 +254,28 @@ pub fn BetAmountControl() -> Element {
     }
 }
 
+#[component]
+pub fn RuleSetControl() -> Element {
+    let w = wallet_signals();
+    let label = match RuleSetId::from_u8(*w.current_rule_set.read()) {
+        Some(id) => format!("{} ({}% RTP)", id, id as u8),
+        // uploaded by the house, the program checked this bound
+        None => format!("house (up to {:.1}% RTP)", w.current_rules.read().return_bound() * 100.0),
+    };
+
+    rsx! {
+        div {
+            style: "display:flex;  align-items: center;",
+            h1 {
+                "Table"
+            }
+            span {
+                {label}
+            }
+        }
+    }
+}
+
 #[component]
 pub fn CurrentWalletDropdown() -> Element {
     let w: WalletSignals = wallet_signals();
@@ -328,6 +376,7 @@ pub fn WalletDashboard() -> Element {
 fn PlayerAccountList() -> Element {
     let mut w = wallet_signals();
     let bank_address = pacanele2_client::get_bank_address().0;
+    let jackpot_address = pacanele2_client::get_jackpot_address().0;
     let delete_me = move |account| {
         w.all_wallets.write().retain(|k| k.keypair().pubkey() != account);
     };
@@ -344,7 +393,11 @@ fn PlayerAccountList() -> Element {
             let sender_pub = sender.pubkey();
             spawn(async move {
                 let client = pacanele2_client::get_client().await;
-                let result = pacanele2_client::send_money(&client, sender, target, amount).await;
+                let result = if target == bank_address {
+                    pacanele2_client::fund_bank(&client, sender, amount).await
+                } else {
+                    pacanele2_client::send_money(&client, sender, target, amount).await
+                };
                 info!(
                     "SEND MONEY: FROM={} TO={} AMOUNT={} \n TX={:#?}",
                     sender_pub, target, amount, result
@@ -365,6 +418,8 @@ fn PlayerAccountList() -> Element {
             h3 { a { href : "/", "Back to main page."}}
             h1 {                "Program Bank"             }
             PlayerAccountDisplay {account:bank_address, on_forget:delete_me, send_money}
+            h1 {                "Program Jackpot"             }
+            PlayerAccountDisplay {account:jackpot_address, on_forget:delete_me, send_money}
 
             h1 {
                 "Player Wallets"
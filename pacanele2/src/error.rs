@@ -0,0 +1,126 @@
+use solana_program::program_error::ProgramError;
+
+/// Errors of the program, sent as `ProgramError::Custom(code)`. Codes never change
+/// meaning, `pacanele2_client` turns them back into these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PacaneleError {
+    /// the instructions sysvar could not be read
+    BadInstructionsSysvar = 1,
+    /// no instruction of the transaction calls a program besides the builtins
+    ProgramNotFound = 2,
+    /// a sysvar or the system program is not the expected account
+    WrongSysvar = 3,
+    /// bank address or bump does not match the seeds
+    WrongBank = 4,
+    WrongFreeSpinsAccount = 5,
+    WrongJackpotAccount = 6,
+    WrongConfigAccount = 7,
+    /// account 5 of a spin must be the program
+    WrongProgramAccount = 8,
+    /// the bet is under the smallest bet the fees allow
+    BetTooSmall = 9,
+    /// the bet exponent is 63 or more
+    BetTooLarge = 10,
+    /// the player can't pay the bet
+    PlayerTooPoor = 11,
+    /// the bank can't cover the bet
+    BankTooPoor = 12,
+    UnknownRuleSet = 13,
+    /// paid spins are paused by the authority
+    Paused = 14,
+    /// the signer is not the config authority (or the upgrade authority for the first config)
+    Unauthorized = 15,
+    ConfigNotInitialized = 16,
+    ConfigAlreadyInitialized = 17,
+    /// a withdrawal would take the bank under its rent
+    WithdrawTooMuch = 18,
+    /// `min_bet` over `max_bet`, or outside `Config::MIN_BET_FLOOR..=Config::MAX_BET_CEILING`
+    InvalidBetLimits = 19,
+    /// over `Config::MAX_HOUSE_FEE_BPS`
+    FeeTooHigh = 20,
+    /// a paid spin asked for another rule set than the config one
+    RuleSetNotActive = 21,
+    WrongRuleSetAccount = 22,
+    /// the uploaded rule set does not load, does not validate or has too many wheels
+    InvalidRuleSet = 23,
+    /// the uploaded rule set may return more than `Config::max_rtp_bps`
+    RtpTooHigh = 24,
+    /// the config plays the rule set account, but nothing was uploaded yet
+    RuleSetNotInitialized = 25,
+}
+
+impl PacaneleError {
+    pub const ALL: [PacaneleError; 25] = [
+        PacaneleError::BadInstructionsSysvar,
+        PacaneleError::ProgramNotFound,
+        PacaneleError::WrongSysvar,
+        PacaneleError::WrongBank,
+        PacaneleError::WrongFreeSpinsAccount,
+        PacaneleError::WrongJackpotAccount,
+        PacaneleError::WrongConfigAccount,
+        PacaneleError::WrongProgramAccount,
+        PacaneleError::BetTooSmall,
+        PacaneleError::BetTooLarge,
+        PacaneleError::PlayerTooPoor,
+        PacaneleError::BankTooPoor,
+        PacaneleError::UnknownRuleSet,
+        PacaneleError::Paused,
+        PacaneleError::Unauthorized,
+        PacaneleError::ConfigNotInitialized,
+        PacaneleError::ConfigAlreadyInitialized,
+        PacaneleError::WithdrawTooMuch,
+        PacaneleError::InvalidBetLimits,
+        PacaneleError::FeeTooHigh,
+        PacaneleError::RuleSetNotActive,
+        PacaneleError::WrongRuleSetAccount,
+        PacaneleError::InvalidRuleSet,
+        PacaneleError::RtpTooHigh,
+        PacaneleError::RuleSetNotInitialized,
+    ];
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| *e as u32 == code)
+    }
+}
+
+impl std::fmt::Display for PacaneleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = match self {
+            PacaneleError::BadInstructionsSysvar => "cannot read the instructions sysvar",
+            PacaneleError::ProgramNotFound => "no pacanele instruction in the transaction",
+            PacaneleError::WrongSysvar => "wrong sysvar or system program account",
+            PacaneleError::WrongBank => "wrong bank account",
+            PacaneleError::WrongFreeSpinsAccount => "wrong free spins account",
+            PacaneleError::WrongJackpotAccount => "wrong jackpot account",
+            PacaneleError::WrongConfigAccount => "wrong config account",
+            PacaneleError::WrongProgramAccount => "wrong program account",
+            PacaneleError::BetTooSmall => "bet too small",
+            PacaneleError::BetTooLarge => "bet too large",
+            PacaneleError::PlayerTooPoor => "not enough coin for this bet",
+            PacaneleError::BankTooPoor => "bank too poor for this bet",
+            PacaneleError::UnknownRuleSet => "unknown rule set",
+            PacaneleError::Paused => "the house is paused",
+            PacaneleError::Unauthorized => "not the authority",
+            PacaneleError::ConfigNotInitialized => "config not initialized",
+            PacaneleError::ConfigAlreadyInitialized => "config already initialized",
+            PacaneleError::WithdrawTooMuch => "the bank can't spare that much",
+            PacaneleError::InvalidBetLimits => "invalid bet limits",
+            PacaneleError::FeeTooHigh => "house fee too high",
+            PacaneleError::RuleSetNotActive => "this table is closed",
+            PacaneleError::WrongRuleSetAccount => "wrong rule set account",
+            PacaneleError::InvalidRuleSet => "invalid rule set",
+            PacaneleError::RtpTooHigh => "rule set pays too much",
+            PacaneleError::RuleSetNotInitialized => "no rule set uploaded",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for PacaneleError {}
+
+impl From<PacaneleError> for ProgramError {
+    fn from(e: PacaneleError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,103 @@
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+/// Instruction data of the program, bincode encoded: a u32 tag, then the fields.
+/// The bumps are passed in so the hot path does not search for them.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PacaneleInstruction {
+    /// Play one paid spin, or a free spin when the player has some left.
+    ///
+    /// accounts:
+    /// 0. instructions sysvar
+    /// 1. slot hashes sysvar
+    /// 2. system program
+    /// 3. `[writable]` bank, seeds = [b"bank"]
+    /// 4. `[writable, signer]` player
+    /// 5. `[writable]` this program
+    /// 6. `[writable]` player free spins, seeds = [FreeSpins::SEED, player]
+    /// 7. `[writable]` jackpot pool, seeds = [b"jackpot"]
+    /// 8. config, seeds = [Config::SEED], limits, rule set and fee of paid spins
+    /// 9. rule set account, seeds = [RuleSetAccount::SEED]
+    Spin {
+        bank_bump: u8,
+        /// the bet is `1 << bet_exp` lamports
+        bet_exp: u8,
+        free_spins_bump: u8,
+        jackpot_bump: u8,
+        config_bump: u8,
+        /// `RuleSetId` or `Config::ACCOUNT_RULE_SET`, free spins play the one they were won on
+        rule_set: u8,
+        rule_set_bump: u8,
+    },
+    /// Create the config with the default limits and open the jackpot pool. Only the
+    /// upgrade authority of the program may do it, once.
+    ///
+    /// accounts:
+    /// 0. `[writable, signer]` upgrade authority, pays the rent of both
+    /// 1. `[writable]` config, seeds = [Config::SEED]
+    /// 2. program data of this program
+    /// 3. system program
+    /// 4. `[writable]` jackpot pool, seeds = [b"jackpot"]
+    InitializeConfig { authority: Pubkey },
+    /// Move lamports from anyone into the bank.
+    ///
+    /// accounts:
+    /// 0. `[writable, signer]` funder
+    /// 1. `[writable]` bank, seeds = [b"bank"]
+    /// 2. system program
+    FundBank { lamports: u64 },
+    /// Move lamports from the bank to the config authority. The bank keeps its rent.
+    ///
+    /// accounts:
+    /// 0. `[writable, signer]` config authority
+    /// 1. config, seeds = [Config::SEED]
+    /// 2. `[writable]` bank, seeds = [b"bank"]
+    /// 3. system program
+    WithdrawFromBank { lamports: u64 },
+    /// Stop or resume paid spins, free spins already won can still be played.
+    ///
+    /// accounts:
+    /// 0. `[signer]` config authority
+    /// 1. `[writable]` config, seeds = [Config::SEED]
+    SetPaused { paused: bool },
+    /// Change the bet limits, the rule set of paid spins, the house fee and the
+    /// return ceiling of `SetRuleSet`. `Config::ACCOUNT_RULE_SET` plays the uploaded
+    /// rule set, it has to be under the new return ceiling too.
+    ///
+    /// accounts:
+    /// 0. `[signer]` config authority
+    /// 1. `[writable]` config, seeds = [Config::SEED]
+    /// 2. rule set account, seeds = [RuleSetAccount::SEED]
+    UpdateConfig {
+        min_bet: u64,
+        max_bet: u64,
+        rule_set: u8,
+        house_fee_bps: u16,
+        max_rtp_bps: u16,
+    },
+    /// Replace the rule set in the rule set account. The program checks that it
+    /// validates and that `RuleSet::return_bound` is at most `Config::max_rtp_bps`.
+    /// Paid spins play it once `UpdateConfig` sets `Config::ACCOUNT_RULE_SET`; free
+    /// spins won on the table it replaces are forfeited. The file has to fit in one
+    /// transaction.
+    ///
+    /// accounts:
+    /// 0. `[writable, signer]` config authority, pays the rent
+    /// 1. config, seeds = [Config::SEED]
+    /// 2. `[writable]` rule set account, seeds = [RuleSetAccount::SEED]
+    /// 3. system program
+    SetRuleSet {
+        /// `RuleSet::serialize` bytes
+        rule_set: Vec<u8>,
+    },
+}
+
+impl PacaneleInstruction {
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        bincode::deserialize(data).map_err(|_e| ProgramError::InvalidInstructionData)
+    }
+
+    pub fn pack(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+}
@@ -3,7 +3,7 @@ use solana_program::{
     account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg, pubkey::Pubkey,
 };
 
-use rules::rule_set::RuleSet;
+use rules::rule_set::{AppliedCap, REWARD_ONE};
 use solana_program::account_info::next_account_info;
 use solana_program::clock::Clock;
 use solana_program::program::set_return_data;
@@ -12,8 +12,24 @@ use solana_program::sysvar::Sysvar;
 use solana_program::serialize_utils::read_u16;
 use solana_program::serialize_utils::read_pubkey;
 
+pub mod error;
+pub mod instruction;
+pub mod state;
+use error::PacaneleError;
+use instruction::PacaneleInstruction;
+use state::{Config, FreeSpins, RuleSetAccount};
+
+pub use rules::bank;
+pub use rules::registry::RuleSetId;
+pub use rules::rule_set::RuleSet;
+
+#[cfg(not(feature = "no-entrypoint"))]
 entrypoint!(process_instruction);
 
+/// seed buffer length, enough for any rule set in the registry; uploaded rule sets
+/// can't have more wheels
+const WHEELS: usize = RuleSetId::MAX_WHEELS;
+
 struct InputParameters<'a, 'b> {
     sysvar_slot_hashes: &'b AccountInfo<'a>,
     bank_account: &'b AccountInfo<'a>,
@@ -23,13 +39,33 @@ struct InputParameters<'a, 'b> {
     bet_amount: u64,
     program_id: Pubkey,
     program_account: &'b AccountInfo<'a>,
+    free_spins_account: &'b AccountInfo<'a>,
+    free_spins_bump: u8,
+    jackpot_account: &'b AccountInfo<'a>,
+    jackpot_bump: u8,
+    /// `RuleSetId` or `Config::ACCOUNT_RULE_SET` picked by the player, free spins keep
+    /// the one they were won on
+    rule_set: u8,
+    config_account: &'b AccountInfo<'a>,
+    rule_set_account: &'b AccountInfo<'a>,
+}
+
+/// fields of `PacaneleInstruction::Spin`
+struct SpinArgs {
+    bank_bump: u8,
+    bet_exp: u8,
+    free_spins_bump: u8,
+    jackpot_bump: u8,
+    config_bump: u8,
+    rule_set: u8,
+    rule_set_bump: u8,
 }
 
 
 pub fn get_program_id<'info>(info: AccountInfo<'info>) -> Result<Pubkey, ProgramError> {
     let instruction_sysvar = info.data.borrow();
     let mut idx = 0;
-    let num_instructions = read_u16(&mut idx, &instruction_sysvar).map_err(|_e| ProgramError::Custom(1))?;
+    let num_instructions = read_u16(&mut idx, &instruction_sysvar).map_err(|_e| PacaneleError::BadInstructionsSysvar)?;
 
     for index in 0..num_instructions {
         let mut current = 2 + (index * 2) as usize;
@@ -50,42 +86,143 @@ pub fn get_program_id<'info>(info: AccountInfo<'info>) -> Result<Pubkey, Program
 
     }
 
-    Err(ProgramError::Custom(2))
+    Err(PacaneleError::ProgramNotFound.into())
 }
 
-fn extract_input<'a, 'b>(_accounts: &'b [AccountInfo<'a>], _instruction_data: &[u8]) -> Result<InputParameters<'a, 'b>, ProgramError> {
+fn extract_input<'a, 'b>(_accounts: &'b [AccountInfo<'a>], args: &SpinArgs) -> Result<InputParameters<'a, 'b>, ProgramError> {
     let accounts_iter = &mut _accounts.iter();
     let sysvar_instructions = next_account_info(accounts_iter)?;
-    solana_program::sysvar::instructions::check_id(&sysvar_instructions.key);
+    if !solana_program::sysvar::instructions::check_id(sysvar_instructions.key) {
+        return Err(PacaneleError::WrongSysvar.into());
+    }
     let program_id = get_program_id(sysvar_instructions.clone())?;
     
     let sysvar_slot_hashes = next_account_info(accounts_iter)?;
-    solana_program::sysvar::slot_hashes::check_id(&sysvar_slot_hashes.key);
+    if !solana_program::sysvar::slot_hashes::check_id(sysvar_slot_hashes.key) {
+        return Err(PacaneleError::WrongSysvar.into());
+    }
     
     let system_program = next_account_info(accounts_iter)?;
-    solana_program::system_program::check_id(&system_program.key);
+    if !solana_program::system_program::check_id(system_program.key) {
+        return Err(PacaneleError::WrongSysvar.into());
+    }
 
     let bank_account = next_account_info(accounts_iter)?;
-    let bank_bump = _instruction_data[0];
+    let bank_bump = args.bank_bump;
     let x = solana_program::pubkey::Pubkey::find_program_address(&[b"bank"], &program_id);
-    assert!(x.0 == *bank_account.key);
-    assert!(x.1 == bank_bump);
+    if x.0 != *bank_account.key || x.1 != bank_bump {
+        return Err(PacaneleError::WrongBank.into());
+    }
 
     let player_account = next_account_info(accounts_iter)?;
-    assert!(_instruction_data[1] > 10 && _instruction_data[1] < 63);
-    let bet_amount = 1_u64 <<( _instruction_data[1] as u64);
-    assert!(bet_amount > 33 * 5000);
-    assert!(bet_amount < player_account.lamports());
-    assert!(bet_amount < bank_account.lamports());
+    if args.bet_exp >= 63 {
+        return Err(PacaneleError::BetTooLarge.into());
+    }
+    let bet_amount = 1_u64 <<( args.bet_exp as u64);
+    if bet_amount >= player_account.lamports() {
+        return Err(PacaneleError::PlayerTooPoor.into());
+    }
+    if bet_amount >= bank_account.lamports() {
+        return Err(PacaneleError::BankTooPoor.into());
+    }
     
     let program_account = next_account_info(accounts_iter)?;
-    assert!(*program_account.key == program_id);
+    if *program_account.key != program_id {
+        return Err(PacaneleError::WrongProgramAccount.into());
+    }
+
+    let free_spins_account = next_account_info(accounts_iter)?;
+    let free_spins_bump = args.free_spins_bump;
+    let x = FreeSpins::find_address(player_account.key, &program_id);
+    if x.0 != *free_spins_account.key || x.1 != free_spins_bump {
+        return Err(PacaneleError::WrongFreeSpinsAccount.into());
+    }
+
+    let jackpot_account = next_account_info(accounts_iter)?;
+    let jackpot_bump = args.jackpot_bump;
+    let x = Pubkey::find_program_address(&[b"jackpot"], &program_id);
+    if x.0 != *jackpot_account.key || x.1 != jackpot_bump {
+        return Err(PacaneleError::WrongJackpotAccount.into());
+    }
+
+    if !known_rule_set(args.rule_set) {
+        return Err(PacaneleError::UnknownRuleSet.into());
+    }
+    let rule_set = args.rule_set;
+
+    let config_account = next_account_info(accounts_iter)?;
+    // only the canonical address, an empty account at another bump would load as the defaults
+    let x = Config::find_address(&program_id);
+    if x.0 != *config_account.key || x.1 != args.config_bump {
+        return Err(PacaneleError::WrongConfigAccount.into());
+    }
+
+    let rule_set_account = next_account_info(accounts_iter)?;
+    let x = RuleSetAccount::find_address(&program_id);
+    if x.0 != *rule_set_account.key || x.1 != args.rule_set_bump {
+        return Err(PacaneleError::WrongRuleSetAccount.into());
+    }
 
     Ok(InputParameters {
-        sysvar_slot_hashes,bank_account,bank_bump,player_account,system_program, bet_amount, program_id, program_account
+        sysvar_slot_hashes,bank_account,bank_bump,player_account,system_program, bet_amount, program_id, program_account, free_spins_account, free_spins_bump, jackpot_account, jackpot_bump, rule_set, config_account, rule_set_account
     })
 }
 
+/// a `RuleSetId` or the rule set account
+fn known_rule_set(rule_set: u8) -> bool {
+    rule_set == Config::ACCOUNT_RULE_SET || RuleSetId::from_u8(rule_set).is_some()
+}
+
+/// A rule set file the program can play. `deserialize` validates it, every
+/// probability table sums to u16::MAX; the wheels must fit the seed buffers.
+fn playable_rule_set(data: &[u8]) -> Result<RuleSet, ProgramError> {
+    let rule_set = RuleSet::deserialize(data).map_err(|e| {
+        msg!("rule set: {}", e);
+        PacaneleError::InvalidRuleSet
+    })?;
+    if rule_set.wheel_count() as usize > WHEELS {
+        return Err(PacaneleError::InvalidRuleSet.into());
+    }
+    Ok(rule_set)
+}
+
+/// The uploaded rule set file, `SetRuleSet` only stores files that load.
+fn account_rule_set_data<'a>(program_id: &Pubkey, rule_set_account: &'a AccountInfo) -> Result<std::cell::Ref<'a, &'a mut [u8]>, ProgramError> {
+    if rule_set_account.data_is_empty() {
+        return Err(PacaneleError::RuleSetNotInitialized.into());
+    }
+    if rule_set_account.owner != program_id {
+        return Err(PacaneleError::WrongRuleSetAccount.into());
+    }
+    rule_set_account.try_borrow_data()
+}
+
+fn load_account_rule_set(program_id: &Pubkey, rule_set_account: &AccountInfo) -> Result<RuleSet, ProgramError> {
+    playable_rule_set(&account_rule_set_data(program_id, rule_set_account)?)
+}
+
+/// `RuleSet::content_hash` of a `RuleSetId` or of the uploaded rule set, without
+/// loading it.
+fn table_hash(input: &InputParameters, rule_set: u8) -> Result<u64, ProgramError> {
+    match RuleSetId::from_u8(rule_set) {
+        Some(id) => Ok(id.content_hash()),
+        None => {
+            let data = account_rule_set_data(&input.program_id, input.rule_set_account)?;
+            rules::container::header_content_hash(&data).ok_or(PacaneleError::InvalidRuleSet.into())
+        }
+    }
+}
+
+/// `RtpTooHigh` when `rule_set` may return more than `max_rtp_bps`, the bound otherwise.
+fn check_return_bound(rule_set: &RuleSet, max_rtp_bps: u16) -> Result<f64, ProgramError> {
+    let rtp = rule_set.return_bound();
+    if rtp * 10000.0 > max_rtp_bps as f64 {
+        msg!("rule set returns up to {}", rtp);
+        return Err(PacaneleError::RtpTooHigh.into());
+    }
+    Ok(rtp)
+}
+
 fn invoke_transfer_player_to_bank(input: &InputParameters, bet_amount: u64) -> Result<(), ProgramError> {
     // msg!("insert coin: {} lamports", amount);
     // keep 3.33% in program account
@@ -121,6 +258,40 @@ fn invoke_transfer_player_to_bank(input: &InputParameters, bet_amount: u64) -> R
     Ok(())
 }
 
+fn invoke_transfer_player_to_jackpot(input: &InputParameters, amount: u64) -> Result<(), ProgramError> {
+    solana_program::program::invoke(
+        &solana_program::system_instruction::transfer(
+            input.player_account.key,
+            input.jackpot_account.key,
+            amount,
+        ),
+        &[
+            input.player_account.to_owned(),
+            input.jackpot_account.to_owned(),
+            input.system_program.to_owned(),
+        ],
+    )?;
+    Ok(())
+}
+
+fn invoke_transfer_jackpot_to_player(input: &InputParameters, amount: u64) -> Result<(), ProgramError> {
+    let jackpot_signer_seeds: &[&[&[u8]]] = &[&[b"jackpot", &[input.jackpot_bump]]];
+    solana_program::program::invoke_signed(
+        &solana_program::system_instruction::transfer(
+            input.jackpot_account.key,
+            input.player_account.key,
+            amount,
+        ),
+        &[
+            input.jackpot_account.to_owned(),
+            input.player_account.to_owned(),
+            input.system_program.to_owned(),
+        ],
+        jackpot_signer_seeds,
+    )?;
+    Ok(())
+}
+
 fn invoke_transfer_bank_to_player(input: &InputParameters, amount: u64) -> Result<(), ProgramError> {
     // msg!("won {} lamports", amount);
     let return_win_instruction = solana_program::system_instruction::transfer(
@@ -141,19 +312,422 @@ fn invoke_transfer_bank_to_player(input: &InputParameters, amount: u64) -> Resul
     Ok(())
 }
 
+fn load_free_spins(input: &InputParameters) -> Result<FreeSpins, ProgramError> {
+    if input.free_spins_account.data_is_empty() {
+        // first spin of this player: player pays the rent
+        let rent = solana_program::rent::Rent::get()?;
+        solana_program::program::invoke_signed(
+            &solana_program::system_instruction::create_account(
+                input.player_account.key,
+                input.free_spins_account.key,
+                rent.minimum_balance(FreeSpins::LEN),
+                FreeSpins::LEN as u64,
+                &input.program_id,
+            ),
+            &[
+                input.player_account.to_owned(),
+                input.free_spins_account.to_owned(),
+                input.system_program.to_owned(),
+            ],
+            &[&[FreeSpins::SEED, input.player_account.key.as_ref(), &[input.free_spins_bump]]],
+        )?;
+        return Ok(FreeSpins::default());
+    }
+    let data = input.free_spins_account.try_borrow_data()?;
+    bincode::deserialize(&data).map_err(|_e| ProgramError::InvalidAccountData)
+}
+
+fn save_free_spins(input: &InputParameters, free_spins: &FreeSpins) -> Result<(), ProgramError> {
+    let mut data = input.free_spins_account.try_borrow_mut_data()?;
+    bincode::serialize_into(&mut data[..], free_spins).map_err(|_e| ProgramError::AccountDataTooSmall)
+}
+
+/// Logs the compute units of the same spin played from the `RuleSet`, then from the
+/// const tables. Build with `--features cu-compare` and read the program logs.
+#[cfg(feature = "cu-compare")]
+fn log_compute_unit_comparison(id: RuleSetId, seed: &[u16], free_spin: bool) {
+    use solana_program::log::sol_log_compute_units;
+
+    msg!("RuleSet::{}() spin:", id);
+    sol_log_compute_units();
+    let r = id.rule_set();
+    let old = if free_spin {
+        r.play_free_spin_from_seed(seed)
+    } else {
+        r.play_random_from_seed(seed)
+    };
+    sol_log_compute_units();
+
+    msg!("compiled spin:");
+    sol_log_compute_units();
+    let mut symbols = [rules::Fruit::all()[0]; WHEELS];
+    let spin = id
+        .compiled()
+        .play_from_seed(seed, &mut symbols[..seed.len()], free_spin);
+    sol_log_compute_units();
+    assert_eq!(old.reward, spin.reward);
+}
+
 pub fn process_instruction(
-    _program_id: &Pubkey,
-    _accounts: &[AccountInfo],
-    _instruction_data: &[u8],
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
 ) -> ProgramResult {
+    match PacaneleInstruction::unpack(instruction_data)? {
+        PacaneleInstruction::Spin {
+            bank_bump,
+            bet_exp,
+            free_spins_bump,
+            jackpot_bump,
+            config_bump,
+            rule_set,
+            rule_set_bump,
+        } => process_spin(
+            accounts,
+            &SpinArgs {
+                bank_bump,
+                bet_exp,
+                free_spins_bump,
+                jackpot_bump,
+                config_bump,
+                rule_set,
+                rule_set_bump,
+            },
+        ),
+        PacaneleInstruction::InitializeConfig { authority } => {
+            process_initialize_config(program_id, accounts, authority)
+        }
+        PacaneleInstruction::FundBank { lamports } => {
+            process_fund_bank(program_id, accounts, lamports)
+        }
+        PacaneleInstruction::WithdrawFromBank { lamports } => {
+            process_withdraw_from_bank(program_id, accounts, lamports)
+        }
+        PacaneleInstruction::SetPaused { paused } => {
+            process_set_paused(program_id, accounts, paused)
+        }
+        PacaneleInstruction::UpdateConfig { min_bet, max_bet, rule_set, house_fee_bps, max_rtp_bps } => {
+            process_update_config(program_id, accounts, min_bet, max_bet, rule_set, house_fee_bps, max_rtp_bps)
+        }
+        PacaneleInstruction::SetRuleSet { rule_set } => {
+            process_set_rule_set(program_id, accounts, &rule_set)
+        }
+    }
+}
+
+/// The config, or the defaults if it was not initialized yet. The caller checks that
+/// `config_account` is the canonical `Config::find_address`.
+fn load_config(program_id: &Pubkey, config_account: &AccountInfo) -> Result<Config, ProgramError> {
+    if config_account.data_is_empty() {
+        return Ok(Config::default());
+    }
+    if config_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let data = config_account.try_borrow_data()?;
+    bincode::deserialize(&data).map_err(|_e| ProgramError::InvalidAccountData)
+}
+
+fn save_config(config_account: &AccountInfo, config: &Config) -> Result<(), ProgramError> {
+    let mut data = config_account.try_borrow_mut_data()?;
+    bincode::serialize_into(&mut data[..], config).map_err(|_e| ProgramError::AccountDataTooSmall)
+}
+
+/// The config account, checked to be initialized and signed for by its authority.
+fn authorized_config(program_id: &Pubkey, authority: &AccountInfo, config_account: &AccountInfo) -> Result<Config, ProgramError> {
+    if *config_account.key != Config::find_address(program_id).0 {
+        return Err(PacaneleError::WrongConfigAccount.into());
+    }
+    if config_account.data_is_empty() {
+        return Err(PacaneleError::ConfigNotInitialized.into());
+    }
+    let config = load_config(program_id, config_account)?;
+    if !authority.is_signer || *authority.key != config.authority {
+        return Err(PacaneleError::Unauthorized.into());
+    }
+    Ok(config)
+}
+
+fn process_initialize_config(program_id: &Pubkey, accounts: &[AccountInfo], authority: Pubkey) -> ProgramResult {
+    use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+
+    let accounts_iter = &mut accounts.iter();
+    let payer = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let program_data = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let jackpot_account = next_account_info(accounts_iter)?;
+
+    // whoever can replace the program anyway picks the authority, nobody can front run it
+    if *program_data.key != bpf_loader_upgradeable::get_program_data_address(program_id) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let upgrade_authority = match bincode::deserialize(&program_data.try_borrow_data()?) {
+        Ok(UpgradeableLoaderState::ProgramData { upgrade_authority_address, .. }) => upgrade_authority_address,
+        _ => return Err(ProgramError::InvalidAccountData),
+    };
+    if !payer.is_signer || upgrade_authority != Some(*payer.key) {
+        return Err(PacaneleError::Unauthorized.into());
+    }
+
+    let (config_address, config_bump) = Config::find_address(program_id);
+    if *config_account.key != config_address {
+        return Err(PacaneleError::WrongConfigAccount.into());
+    }
+    if !config_account.data_is_empty() {
+        return Err(PacaneleError::ConfigAlreadyInitialized.into());
+    }
+    if *jackpot_account.key != Pubkey::find_program_address(&[b"jackpot"], program_id).0 {
+        return Err(PacaneleError::WrongJackpotAccount.into());
+    }
+    let rent = solana_program::rent::Rent::get()?;
+    solana_program::program::invoke_signed(
+        &solana_program::system_instruction::create_account(
+            payer.key,
+            config_account.key,
+            rent.minimum_balance(Config::LEN),
+            Config::LEN as u64,
+            program_id,
+        ),
+        &[payer.to_owned(), config_account.to_owned(), system_program.to_owned()],
+        &[&[Config::SEED, &[config_bump]]],
+    )?;
+    save_config(config_account, &Config { authority, ..Config::default() })?;
+
+    // spins only pay into the pool once it is rent exempt
+    let missing = rent.minimum_balance(0).saturating_sub(jackpot_account.lamports());
+    if missing > 0 {
+        solana_program::program::invoke(
+            &solana_program::system_instruction::transfer(payer.key, jackpot_account.key, missing),
+            &[payer.to_owned(), jackpot_account.to_owned(), system_program.to_owned()],
+        )?;
+    }
+    msg!("config: authority {}", authority);
+    Ok(())
+}
+
+fn process_fund_bank(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let funder = next_account_info(accounts_iter)?;
+    let bank_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    if *bank_account.key != Pubkey::find_program_address(&[b"bank"], program_id).0 {
+        return Err(PacaneleError::WrongBank.into());
+    }
+    if !funder.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    solana_program::program::invoke(
+        &solana_program::system_instruction::transfer(funder.key, bank_account.key, lamports),
+        &[funder.to_owned(), bank_account.to_owned(), system_program.to_owned()],
+    )?;
+    msg!("bank: +{} lamports", lamports);
+    Ok(())
+}
+
+fn process_withdraw_from_bank(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let authority = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let bank_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    authorized_config(program_id, authority, config_account)?;
+    let (bank_address, bank_bump) = Pubkey::find_program_address(&[b"bank"], program_id);
+    if *bank_account.key != bank_address {
+        return Err(PacaneleError::WrongBank.into());
+    }
+    let rent = solana_program::rent::Rent::get()?;
+    let available = bank_account.lamports().saturating_sub(rent.minimum_balance(0));
+    if lamports > available {
+        return Err(PacaneleError::WithdrawTooMuch.into());
+    }
+    solana_program::program::invoke_signed(
+        &solana_program::system_instruction::transfer(bank_account.key, authority.key, lamports),
+        &[bank_account.to_owned(), authority.to_owned(), system_program.to_owned()],
+        &[&[b"bank", &[bank_bump]]],
+    )?;
+    msg!("bank: -{} lamports", lamports);
+    Ok(())
+}
+
+fn process_set_paused(program_id: &Pubkey, accounts: &[AccountInfo], paused: bool) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let authority = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    let mut config = authorized_config(program_id, authority, config_account)?;
+    config.paused = paused;
+    save_config(config_account, &config)?;
+    msg!("paused: {}", paused);
+    Ok(())
+}
+
+/// The config limits of a paid spin, free spins were paid for by the spin that won them.
+fn check_paid_spin(config: &Config, input: &InputParameters) -> ProgramResult {
+    if config.paused {
+        return Err(PacaneleError::Paused.into());
+    }
+    if input.bet_amount < config.min_bet {
+        return Err(PacaneleError::BetTooSmall.into());
+    }
+    if input.bet_amount > config.max_bet {
+        return Err(PacaneleError::BetTooLarge.into());
+    }
+    if input.rule_set != config.rule_set {
+        return Err(PacaneleError::RuleSetNotActive.into());
+    }
+    if input.bet_amount + config.house_fee(input.bet_amount) >= input.player_account.lamports() {
+        return Err(PacaneleError::PlayerTooPoor.into());
+    }
+    Ok(())
+}
+
+fn process_update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_bet: u64,
+    max_bet: u64,
+    rule_set: u8,
+    house_fee_bps: u16,
+    max_rtp_bps: u16,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let authority = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let rule_set_account = next_account_info(accounts_iter)?;
+
+    let mut config = authorized_config(program_id, authority, config_account)?;
+    if *rule_set_account.key != RuleSetAccount::find_address(program_id).0 {
+        return Err(PacaneleError::WrongRuleSetAccount.into());
+    }
+    if min_bet < Config::MIN_BET_FLOOR || max_bet > Config::MAX_BET_CEILING || min_bet > max_bet {
+        return Err(PacaneleError::InvalidBetLimits.into());
+    }
+    if !known_rule_set(rule_set) {
+        return Err(PacaneleError::UnknownRuleSet.into());
+    }
+    if house_fee_bps > Config::MAX_HOUSE_FEE_BPS {
+        return Err(PacaneleError::FeeTooHigh.into());
+    }
+    // a house that pays back more than it takes runs dry
+    if max_rtp_bps >= 10000 {
+        return Err(PacaneleError::RtpTooHigh.into());
+    }
+    // the ceiling may have come down since the upload
+    if rule_set == Config::ACCOUNT_RULE_SET {
+        check_return_bound(&load_account_rule_set(program_id, rule_set_account)?, max_rtp_bps)?;
+    }
+    config.min_bet = min_bet;
+    config.max_bet = max_bet;
+    config.rule_set = rule_set;
+    config.house_fee_bps = house_fee_bps;
+    config.max_rtp_bps = max_rtp_bps;
+    save_config(config_account, &config)?;
+    msg!("config: bets {}..={}, rule set {}, fee {} bps, rtp <= {} bps", min_bet, max_bet, rule_set, house_fee_bps, max_rtp_bps);
+    Ok(())
+}
+
+fn process_set_rule_set(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let authority = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let rule_set_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let config = authorized_config(program_id, authority, config_account)?;
+    let (rule_set_address, rule_set_bump) = RuleSetAccount::find_address(program_id);
+    if *rule_set_account.key != rule_set_address {
+        return Err(PacaneleError::WrongRuleSetAccount.into());
+    }
+    let rule_set = playable_rule_set(data)?;
+    let rtp = check_return_bound(&rule_set, config.max_rtp_bps)?;
+
+    let rent = solana_program::rent::Rent::get()?;
+    if rule_set_account.data_is_empty() {
+        solana_program::program::invoke_signed(
+            &solana_program::system_instruction::create_account(
+                authority.key,
+                rule_set_account.key,
+                rent.minimum_balance(data.len()),
+                data.len() as u64,
+                program_id,
+            ),
+            &[authority.to_owned(), rule_set_account.to_owned(), system_program.to_owned()],
+            &[&[RuleSetAccount::SEED, &[rule_set_bump]]],
+        )?;
+    } else {
+        let missing = rent
+            .minimum_balance(data.len())
+            .saturating_sub(rule_set_account.lamports());
+        if missing > 0 {
+            solana_program::program::invoke(
+                &solana_program::system_instruction::transfer(authority.key, rule_set_account.key, missing),
+                &[authority.to_owned(), rule_set_account.to_owned(), system_program.to_owned()],
+            )?;
+        }
+        rule_set_account.realloc(data.len(), false)?;
+    }
+    rule_set_account.try_borrow_mut_data()?.copy_from_slice(data);
+    msg!("rule set: {:016x}, returns up to {}", rule_set.content_hash(), rtp);
+    Ok(())
+}
+
+fn process_spin(_accounts: &[AccountInfo], args: &SpinArgs) -> ProgramResult {
     // extract accounts
-    let input = extract_input(_accounts, _instruction_data)?;
+    let input = extract_input(_accounts, args)?;
+    let rent = solana_program::rent::Rent::get()?;
 
     
     // msg!("after init accounts:");    ::solana_program::log::sol_log_compute_units();
 
-    // send credits to bank account
-    invoke_transfer_player_to_bank(&input, input.bet_amount)?;
+    // free spins are paid by the bet that won them, no new coin goes in
+    let mut free_spins = load_free_spins(&input)?;
+    if free_spins.remaining > 0 && table_hash(&input, free_spins.rule_set)? != free_spins.rule_set_hash {
+        msg!("rule set changed, {} free spins forfeited", free_spins.remaining);
+        free_spins = FreeSpins::default();
+    }
+    let free_spin = free_spins.remaining > 0;
+    let config = load_config(&input.program_id, input.config_account)?;
+    if !free_spin {
+        check_paid_spin(&config, &input)?;
+    }
+    let rule_set = if free_spin {
+        free_spins.rule_set
+    } else {
+        input.rule_set
+    };
+    // tables of the rule set account, built for this spin
+    let uploaded: (RuleSet, Vec<_>, Vec<u32>);
+    let rules = match RuleSetId::from_u8(rule_set) {
+        Some(id) => id.compiled(),
+        None if rule_set == Config::ACCOUNT_RULE_SET => {
+            let r = load_account_rule_set(&input.program_id, input.rule_set_account)?;
+            let (cdf, rewards) = (r.cdf_table(), r.reward_table());
+            uploaded = (r, cdf, rewards);
+            uploaded.0.compiled(&uploaded.1, &uploaded.2)
+        }
+        None => return Err(ProgramError::InvalidAccountData),
+    };
+    let wheels = rules.wheel_count as usize;
+    let bet_amount = if free_spin {
+        free_spins.remaining -= 1;
+        free_spins.bet_amount
+    } else {
+        // part of the bet goes to the jackpot pool, once the pool account is rent exempt
+        let jackpot_open = input.jackpot_account.lamports() >= rent.minimum_balance(0);
+        let jackpot_amount = match rules.jackpot {
+            Some(j) if jackpot_open => input.bet_amount / 10000 * j.contribution_bps as u64,
+            _ => 0,
+        };
+        // send credits and the house fee to bank account
+        invoke_transfer_player_to_bank(&input, input.bet_amount - jackpot_amount + config.house_fee(input.bet_amount))?;
+        if jackpot_amount > 0 {
+            invoke_transfer_player_to_jackpot(&input, jackpot_amount)?;
+        }
+        input.bet_amount
+    };
 
     // msg!("after insert coin:");    ::solana_program::log::sol_log_compute_units();
 
@@ -169,16 +743,34 @@ pub fn process_instruction(
     use rand_chacha::rand_core::SeedableRng;
     use rand_chacha::ChaCha8Rng;
     let mut chacha = ChaCha8Rng::from_seed(not_random);
-    let seed = chacha.gen();
+    let mut seed = [0u16; WHEELS];
+    let seed = &mut seed[..wheels];
+    for s in seed.iter_mut() {
+        *s = chacha.gen();
+    }
 
     
     // msg!("after banana seed:");    ::solana_program::log::sol_log_compute_units();
 
     // compute banana
     // msg!("banana seeds: {:?}", seed);
-    let r = rules::rule_set::RuleSet::p96();
-    let rv = r.play_random_from_seed(seed);
-    let win = rv.1 as u64;
+    #[cfg(feature = "cu-compare")]
+    if let Some(id) = RuleSetId::from_u8(rule_set) {
+        log_compute_unit_comparison(id, seed, free_spin);
+    }
+    let mut symbols = [rules::Fruit::all()[0]; WHEELS];
+    let symbols = &mut symbols[..wheels];
+    let spin = rules.play_from_seed(seed, symbols, free_spin);
+    let mut rv = rules.outcome(seed, symbols, &spin);
+    // reward is in basis points of the bet, u128 so big bets can't overflow
+    let win = (bet_amount as u128 * spin.reward as u128 / REWARD_ONE as u128) as u64;
+    if spin.free_spins > 0 {
+        free_spins.remaining += spin.free_spins as u32;
+        free_spins.bet_amount = bet_amount;
+        free_spins.rule_set = rule_set;
+        free_spins.rule_set_hash = table_hash(&input, rule_set)?;
+    }
+    save_free_spins(&input, &free_spins)?;
     // msg!("RESULT: {:?}", rv);
 
     
@@ -186,11 +778,26 @@ pub fn process_instruction(
 
     // send win back
     if win > 0 {
-        let max_payable = input.bank_account.lamports()/2-890880*2;
-        let win_lamports = (input.bet_amount * win).min(max_payable);
+        // the rule set max_win is already in rv.reward, this only keeps the bank alive
+        let max_payable = bank::max_payout(input.bank_account.lamports());
+        let win_lamports = win.min(max_payable);
+        if win_lamports < win {
+            rv.caps.push(AppliedCap::Bank {
+                win,
+                paid: win_lamports,
+            });
+        }
         invoke_transfer_bank_to_player(&input, win_lamports)?;
     }
 
+    // the jackpot pays the whole pool, only the rent stays behind
+    if spin.jackpot {
+        let pool = input.jackpot_account.lamports().saturating_sub(rent.minimum_balance(0));
+        if pool > 0 {
+            invoke_transfer_jackpot_to_player(&input, pool)?;
+        }
+    }
+
     
     // msg!("after send win back:");    ::solana_program::log::sol_log_compute_units();
 
@@ -259,7 +866,7 @@ pub fn get_recent_block_hashes(
 }
 
 fn print_pacanel(r: &RuleSet, seed: u16) {
-    let p = r.play_random_from_seed([seed; 3]);
+    let p = r.play_random_from_seed(&vec![seed; r.wheel_count() as usize]);
     msg!("PACANEL/{seed}: {:?}!", p);
 }
 
@@ -289,4 +896,434 @@ mod test {
         let transaction_result = banks_client.process_transaction(transaction).await;
         assert!(transaction_result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_admin_instructions() {
+        use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+        use solana_program::instruction::{AccountMeta, Instruction};
+        use solana_sdk::account::Account;
+        use solana_program::instruction::InstructionError;
+        use solana_program_test::BanksClientError;
+        use solana_sdk::signature::Keypair;
+        use solana_sdk::transaction::TransactionError;
+
+        let program_id = Pubkey::new_unique();
+        let upgrade_authority = Keypair::new();
+        let mut program_test = ProgramTest::new("pacanele2", program_id, processor!(process_instruction));
+        program_test.add_account(
+            upgrade_authority.pubkey(),
+            Account::new(10_000_000_000, 0, &solana_program::system_program::id()),
+        );
+        let program_data = bpf_loader_upgradeable::get_program_data_address(&program_id);
+        program_test.add_account(
+            program_data,
+            Account {
+                lamports: 1_000_000_000,
+                data: bincode::serialize(&UpgradeableLoaderState::ProgramData {
+                    slot: 0,
+                    upgrade_authority_address: Some(upgrade_authority.pubkey()),
+                })
+                .unwrap(),
+                owner: bpf_loader_upgradeable::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        let context = program_test.start_with_context().await;
+        let payer = context.payer.insecure_clone();
+        let config = Config::find_address(&program_id).0;
+        let bank = Pubkey::find_program_address(&[b"bank"], &program_id).0;
+        let jackpot = Pubkey::find_program_address(&[b"jackpot"], &program_id).0;
+        let system = solana_program::system_program::id();
+
+        let ix = |data: PacaneleInstruction, accounts: Vec<AccountMeta>| {
+            Instruction::new_with_bincode(program_id, &data, accounts)
+        };
+        let init = |signer: &Keypair, authority: Pubkey| {
+            ix(
+                PacaneleInstruction::InitializeConfig { authority },
+                vec![
+                    AccountMeta::new(signer.pubkey(), true),
+                    AccountMeta::new(config, false),
+                    AccountMeta::new_readonly(program_data, false),
+                    AccountMeta::new_readonly(system, false),
+                    AccountMeta::new(jackpot, false),
+                ],
+            )
+        };
+        let withdraw = |signer: &Keypair, lamports| {
+            ix(
+                PacaneleInstruction::WithdrawFromBank { lamports },
+                vec![
+                    AccountMeta::new(signer.pubkey(), true),
+                    AccountMeta::new_readonly(config, false),
+                    AccountMeta::new(bank, false),
+                    AccountMeta::new_readonly(system, false),
+                ],
+            )
+        };
+        let pause = |signer: &Keypair, paused| {
+            ix(
+                PacaneleInstruction::SetPaused { paused },
+                vec![AccountMeta::new_readonly(signer.pubkey(), true), AccountMeta::new(config, false)],
+            )
+        };
+        let rule_set_address = RuleSetAccount::find_address(&program_id).0;
+        let update = |signer: &Keypair, min_bet, max_bet, rule_set: u8, house_fee_bps| {
+            ix(
+                PacaneleInstruction::UpdateConfig {
+                    min_bet,
+                    max_bet,
+                    rule_set,
+                    house_fee_bps,
+                    max_rtp_bps: 9500,
+                },
+                vec![
+                    AccountMeta::new_readonly(signer.pubkey(), true),
+                    AccountMeta::new(config, false),
+                    AccountMeta::new_readonly(rule_set_address, false),
+                ],
+            )
+        };
+        let set_rule_set = |signer: &Keypair, rule_set: Vec<u8>| {
+            ix(
+                PacaneleInstruction::SetRuleSet { rule_set },
+                vec![
+                    AccountMeta::new(signer.pubkey(), true),
+                    AccountMeta::new_readonly(config, false),
+                    AccountMeta::new(rule_set_address, false),
+                    AccountMeta::new_readonly(system, false),
+                ],
+            )
+        };
+        let fund = ix(
+            PacaneleInstruction::FundBank { lamports: 2_000_000_000 },
+            vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(bank, false),
+                AccountMeta::new_readonly(system, false),
+            ],
+        );
+
+        let run = |instruction: Instruction, signers: Vec<&Keypair>| {
+            let mut transaction = Transaction::new_with_payer(&[instruction], Some(&signers[0].pubkey()));
+            transaction.sign(&signers, context.last_blockhash);
+            let banks_client = context.banks_client.clone();
+            async move { banks_client.process_transaction(transaction).await }
+        };
+        let failure = |result: Result<(), BanksClientError>| match result.unwrap_err().unwrap() {
+            TransactionError::InstructionError(0, InstructionError::Custom(code)) => PacaneleError::from_code(code),
+            _ => None,
+        };
+
+        // only the upgrade authority sets up the config, and only once
+        assert_eq!(failure(run(init(&payer, payer.pubkey()), vec![&payer]).await), Some(PacaneleError::Unauthorized));
+        run(init(&upgrade_authority, upgrade_authority.pubkey()), vec![&upgrade_authority]).await.unwrap();
+        let rent = context.banks_client.clone().get_rent().await.unwrap();
+        assert_eq!(context.banks_client.clone().get_balance(jackpot).await.unwrap(), rent.minimum_balance(0));
+        assert!(run(pause(&upgrade_authority, false), vec![&upgrade_authority]).await.is_ok());
+        assert_eq!(
+            failure(run(init(&upgrade_authority, payer.pubkey()), vec![&upgrade_authority]).await),
+            Some(PacaneleError::ConfigAlreadyInitialized)
+        );
+
+        run(fund.clone(), vec![&payer]).await.unwrap();
+        let banks_client = context.banks_client.clone();
+        assert_eq!(banks_client.get_balance(bank).await.unwrap(), 2_000_000_000);
+
+        assert_eq!(failure(run(withdraw(&payer, 1), vec![&payer]).await), Some(PacaneleError::Unauthorized));
+        assert_eq!(
+            failure(run(withdraw(&upgrade_authority, 2_000_000_000), vec![&upgrade_authority]).await),
+            Some(PacaneleError::WithdrawTooMuch)
+        );
+        run(withdraw(&upgrade_authority, 1_500_000_000), vec![&upgrade_authority]).await.unwrap();
+        assert_eq!(banks_client.get_balance(bank).await.unwrap(), 500_000_000);
+
+        assert_eq!(failure(run(pause(&payer, true), vec![&payer]).await), Some(PacaneleError::Unauthorized));
+        run(pause(&upgrade_authority, true), vec![&upgrade_authority]).await.unwrap();
+        let account = banks_client.get_account(config).await.unwrap().unwrap();
+        let stored: Config = bincode::deserialize(&account.data).unwrap();
+        assert_eq!(stored, Config { authority: upgrade_authority.pubkey(), paused: true, ..Config::default() });
+
+        let admin = &upgrade_authority;
+        assert_eq!(
+            failure(run(update(&payer, 1 << 20, 1 << 30, RuleSetId::P94 as u8, 100), vec![&payer]).await),
+            Some(PacaneleError::Unauthorized)
+        );
+        assert_eq!(
+            failure(run(update(admin, 1 << 30, 1 << 20, RuleSetId::P94 as u8, 100), vec![admin]).await),
+            Some(PacaneleError::InvalidBetLimits)
+        );
+        assert_eq!(
+            failure(run(update(admin, 1000, 1 << 20, RuleSetId::P94 as u8, 100), vec![admin]).await),
+            Some(PacaneleError::InvalidBetLimits)
+        );
+        assert_eq!(
+            failure(run(update(admin, 1 << 20, 1 << 30, RuleSetId::P94 as u8, 5000), vec![admin]).await),
+            Some(PacaneleError::FeeTooHigh)
+        );
+        run(update(admin, 1 << 20, 1 << 30, RuleSetId::P94 as u8, 100), vec![admin]).await.unwrap();
+        let account = banks_client.get_account(config).await.unwrap().unwrap();
+        let stored: Config = bincode::deserialize(&account.data).unwrap();
+        let expected = Config {
+            authority: admin.pubkey(),
+            paused: true,
+            min_bet: 1 << 20,
+            max_bet: 1 << 30,
+            rule_set: RuleSetId::P94 as u8,
+            house_fee_bps: 100,
+            max_rtp_bps: 9500,
+        };
+        assert_eq!(stored, expected);
+
+        assert_eq!(
+            failure(run(update(admin, 1 << 20, 1 << 30, Config::ACCOUNT_RULE_SET, 0), vec![admin]).await),
+            Some(PacaneleError::RuleSetNotInitialized)
+        );
+
+        // uploaded rule sets must load and stay under max_rtp_bps
+        let p94 = RuleSetId::P94.rule_set().serialize();
+        let p96 = RuleSetId::P96.rule_set().serialize();
+        assert_eq!(
+            failure(run(set_rule_set(&payer, p94.clone()), vec![&payer]).await),
+            Some(PacaneleError::Unauthorized)
+        );
+        assert_eq!(
+            failure(run(set_rule_set(admin, p94[..100].to_vec()), vec![admin]).await),
+            Some(PacaneleError::InvalidRuleSet)
+        );
+        assert_eq!(
+            failure(run(set_rule_set(admin, p96), vec![admin]).await),
+            Some(PacaneleError::RtpTooHigh)
+        );
+        run(set_rule_set(admin, p94.clone()), vec![admin]).await.unwrap();
+        let account = banks_client.get_account(rule_set_address).await.unwrap().unwrap();
+        assert_eq!(account.data, p94);
+        // paid spins keep their table until the authority switches to the upload
+        let account = banks_client.get_account(config).await.unwrap().unwrap();
+        let stored: Config = bincode::deserialize(&account.data).unwrap();
+        assert_eq!(stored, expected);
+        run(update(admin, 1 << 20, 1 << 30, Config::ACCOUNT_RULE_SET, 100), vec![admin]).await.unwrap();
+        let account = banks_client.get_account(config).await.unwrap().unwrap();
+        let stored: Config = bincode::deserialize(&account.data).unwrap();
+        assert_eq!(stored.rule_set, Config::ACCOUNT_RULE_SET);
+    }
+    /// `PacaneleInstruction::Spin` with the accounts of `player`
+    fn spin_instruction(program_id: &Pubkey, player: &Pubkey, rule_set: u8, bet_exp: u8) -> solana_program::instruction::Instruction {
+        use solana_program::instruction::{AccountMeta, Instruction};
+
+        let (bank, bank_bump) = Pubkey::find_program_address(&[b"bank"], program_id);
+        let (free_spins, free_spins_bump) = FreeSpins::find_address(player, program_id);
+        let (jackpot, jackpot_bump) = Pubkey::find_program_address(&[b"jackpot"], program_id);
+        let (config, config_bump) = Config::find_address(program_id);
+        let (rule_set_address, rule_set_bump) = RuleSetAccount::find_address(program_id);
+        Instruction::new_with_bincode(
+            *program_id,
+            &PacaneleInstruction::Spin { bank_bump, bet_exp, free_spins_bump, jackpot_bump, config_bump, rule_set, rule_set_bump },
+            vec![
+                AccountMeta::new_readonly(solana_program::sysvar::instructions::id(), false),
+                AccountMeta::new_readonly(solana_program::sysvar::slot_hashes::id(), false),
+                AccountMeta::new_readonly(solana_program::system_program::id(), false),
+                AccountMeta::new(bank, false),
+                AccountMeta::new(*player, true),
+                AccountMeta::new(*program_id, false),
+                AccountMeta::new(free_spins, false),
+                AccountMeta::new(jackpot, false),
+                AccountMeta::new_readonly(config, false),
+                AccountMeta::new_readonly(rule_set_address, false),
+            ],
+        )
+    }
+
+    /// plays `spin`, signed by `player`, and decodes the `SpinOutcome` return data
+    async fn play(context: &ProgramTestContext, spin: solana_program::instruction::Instruction, player: &solana_sdk::signature::Keypair) -> rules::rule_set::SpinOutcome {
+        let mut transaction = Transaction::new_with_payer(&[spin], Some(&context.payer.pubkey()));
+        transaction.sign(&[&context.payer, player], context.last_blockhash);
+        let result = context.banks_client.clone().process_transaction_with_metadata(transaction).await.unwrap();
+        result.result.unwrap();
+        let return_data = result.metadata.unwrap().return_data.unwrap();
+        bincode::deserialize(&return_data.data).unwrap()
+    }
+
+    /// a program with a funded bank and `player`, who won `free_spins` before
+    fn program_with_player(program_id: Pubkey, player: &Pubkey, free_spins: Option<FreeSpins>) -> ProgramTest {
+        use solana_sdk::account::Account;
+
+        let system = solana_program::system_program::id();
+        let mut program_test = ProgramTest::new("pacanele2", program_id, processor!(process_instruction));
+        program_test.add_account(*player, Account::new(1_000_000_000, 0, &system));
+        let bank = Pubkey::find_program_address(&[b"bank"], &program_id).0;
+        program_test.add_account(bank, Account::new(10_000_000_000, 0, &system));
+        if let Some(free_spins) = free_spins {
+            program_test.add_account(
+                FreeSpins::find_address(player, &program_id).0,
+                Account {
+                    lamports: solana_program::rent::Rent::default().minimum_balance(FreeSpins::LEN),
+                    data: bincode::serialize(&free_spins).unwrap(),
+                    owner: program_id,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            );
+        }
+        program_test
+    }
+
+    #[tokio::test]
+    async fn test_free_spin() {
+        use solana_sdk::signature::Keypair;
+
+        let program_id = Pubkey::new_unique();
+        let player = Keypair::new();
+        // a paid spin on the default table won these
+        let won = FreeSpins {
+            remaining: 2,
+            bet_amount: 1 << 20,
+            rule_set: RuleSetId::DEFAULT as u8,
+            rule_set_hash: RuleSetId::DEFAULT.content_hash(),
+        };
+        let mut context = program_with_player(program_id, &player.pubkey(), Some(won)).start_with_context().await;
+        context.warp_to_slot(10).unwrap();
+
+        // free spins play the table they were won on
+        let spin = spin_instruction(&program_id, &player.pubkey(), RuleSetId::P90 as u8, 20);
+        let outcome = play(&context, spin, &player).await;
+        let banks_client = context.banks_client.clone();
+
+        // the default table pays its free spins with the scatter multiplier, from the bank
+        let scatter = RuleSetId::DEFAULT.rule_set().scatter().unwrap();
+        assert_eq!(outcome.multiplier, scatter.win_multiplier);
+        let win = won.bet_amount * outcome.reward as u64 / REWARD_ONE as u64;
+        let bank = Pubkey::find_program_address(&[b"bank"], &program_id).0;
+        assert_eq!(banks_client.get_balance(player.pubkey()).await.unwrap(), 1_000_000_000 + win);
+        assert_eq!(banks_client.get_balance(bank).await.unwrap(), 10_000_000_000 - win);
+        let free_spins = FreeSpins::find_address(&player.pubkey(), &program_id).0;
+        let account = banks_client.get_account(free_spins).await.unwrap().unwrap();
+        let left: FreeSpins = bincode::deserialize(&account.data).unwrap();
+        assert_eq!(left.remaining, 1 + outcome.free_spins as u32);
+        assert_eq!(left.bet_amount, won.bet_amount);
+    }
+
+    #[tokio::test]
+    async fn test_stale_free_spins_forfeited() {
+        use solana_sdk::signature::Keypair;
+
+        let program_id = Pubkey::new_unique();
+        let player = Keypair::new();
+        // won on a table that was replaced since
+        let won = FreeSpins {
+            remaining: 2,
+            bet_amount: 1 << 20,
+            rule_set: RuleSetId::DEFAULT as u8,
+            rule_set_hash: RuleSetId::DEFAULT.content_hash() ^ 1,
+        };
+        let mut context = program_with_player(program_id, &player.pubkey(), Some(won)).start_with_context().await;
+        context.warp_to_slot(10).unwrap();
+
+        let bet = 1u64 << 20;
+        let spin = spin_instruction(&program_id, &player.pubkey(), RuleSetId::DEFAULT as u8, 20);
+        let outcome = play(&context, spin, &player).await;
+        let banks_client = context.banks_client.clone();
+
+        // played as a paid spin, the jackpot pool is not open so the whole bet goes in
+        assert_eq!(outcome.multiplier, 1);
+        let win = bet * outcome.reward as u64 / REWARD_ONE as u64;
+        assert_eq!(banks_client.get_balance(player.pubkey()).await.unwrap(), 1_000_000_000 - bet + win);
+        let free_spins = FreeSpins::find_address(&player.pubkey(), &program_id).0;
+        let account = banks_client.get_account(free_spins).await.unwrap().unwrap();
+        let left: FreeSpins = bincode::deserialize(&account.data).unwrap();
+        assert_eq!(left.remaining, outcome.free_spins as u32);
+    }
+
+    #[tokio::test]
+    async fn test_paid_spin_feeds_jackpot() {
+        use solana_sdk::account::Account;
+        use solana_sdk::signature::Keypair;
+
+        let program_id = Pubkey::new_unique();
+        let player = Keypair::new();
+        let mut program_test = program_with_player(program_id, &player.pubkey(), None);
+        // as `InitializeConfig` leaves it
+        let jackpot = Pubkey::find_program_address(&[b"jackpot"], &program_id).0;
+        let rent = solana_program::rent::Rent::default().minimum_balance(0);
+        program_test.add_account(jackpot, Account::new(rent, 0, &solana_program::system_program::id()));
+        let mut context = program_test.start_with_context().await;
+        context.warp_to_slot(10).unwrap();
+
+        let bet = 1u64 << 20;
+        let spin = spin_instruction(&program_id, &player.pubkey(), RuleSetId::DEFAULT as u8, 20);
+        let outcome = play(&context, spin, &player).await;
+        let jackpot_rule = RuleSetId::DEFAULT.rule_set().jackpot().unwrap();
+        let contribution = bet / 10000 * jackpot_rule.contribution_bps as u64;
+        let pool = context.banks_client.clone().get_balance(jackpot).await.unwrap();
+        if outcome.jackpot {
+            assert_eq!(pool, rent);
+        } else {
+            assert_eq!(pool, rent + contribution);
+        }
+    }
+
+    /// another valid address for `seeds`, at a bump below the canonical one
+    fn non_canonical_address(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+        let canonical = Pubkey::find_program_address(seeds, program_id).1;
+        (0..canonical)
+            .rev()
+            .find_map(|bump| {
+                let bump = [bump];
+                let mut seeds = seeds.to_vec();
+                seeds.push(&bump);
+                Pubkey::create_program_address(&seeds, program_id).ok().map(|address| (address, bump[0]))
+            })
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_non_canonical_accounts_rejected() {
+        use solana_program::instruction::InstructionError;
+        use solana_sdk::signature::Keypair;
+        use solana_sdk::transaction::TransactionError;
+
+        let program_id = Pubkey::new_unique();
+        let player = Keypair::new();
+        let context = program_with_player(program_id, &player.pubkey(), None).start_with_context().await;
+        let player_key = player.pubkey();
+
+        type BumpField = fn(&mut PacaneleInstruction) -> &mut u8;
+        // (account index in `Spin`, seeds, bump field, expected error)
+        let cases: [(usize, Vec<&[u8]>, BumpField, PacaneleError); 4] = [
+            // an empty config at another bump would load as the defaults
+            (8, vec![Config::SEED], |i| match i {
+                PacaneleInstruction::Spin { config_bump, .. } => config_bump,
+                _ => unreachable!(),
+            }, PacaneleError::WrongConfigAccount),
+            // a pool that never gets rent exempt would skip the contributions
+            (7, vec![b"jackpot"], |i| match i {
+                PacaneleInstruction::Spin { jackpot_bump, .. } => jackpot_bump,
+                _ => unreachable!(),
+            }, PacaneleError::WrongJackpotAccount),
+            // a second record would let the player choose which free spins to play
+            (6, vec![FreeSpins::SEED, player_key.as_ref()], |i| match i {
+                PacaneleInstruction::Spin { free_spins_bump, .. } => free_spins_bump,
+                _ => unreachable!(),
+            }, PacaneleError::WrongFreeSpinsAccount),
+            // not the table the authority uploaded
+            (9, vec![RuleSetAccount::SEED], |i| match i {
+                PacaneleInstruction::Spin { rule_set_bump, .. } => rule_set_bump,
+                _ => unreachable!(),
+            }, PacaneleError::WrongRuleSetAccount),
+        ];
+        for (index, seeds, bump_field, expected) in cases {
+            let (address, bump) = non_canonical_address(&seeds, &program_id);
+            let mut spin = spin_instruction(&program_id, &player.pubkey(), RuleSetId::DEFAULT as u8, 20);
+            let mut data: PacaneleInstruction = bincode::deserialize(&spin.data).unwrap();
+            *bump_field(&mut data) = bump;
+            spin.data = bincode::serialize(&data).unwrap();
+            spin.accounts[index].pubkey = address;
+
+            let mut transaction = Transaction::new_with_payer(&[spin], Some(&context.payer.pubkey()));
+            transaction.sign(&[&context.payer, &player], context.last_blockhash);
+            let error = context.banks_client.clone().process_transaction(transaction).await.unwrap_err().unwrap();
+            assert_eq!(error, TransactionError::InstructionError(0, InstructionError::Custom(expected as u32)));
+        }
+    }
 }
@@ -0,0 +1,99 @@
+use solana_program::pubkey::Pubkey;
+
+/// Free spins won by a player, one account per player.
+/// seeds = [FreeSpins::SEED, player]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FreeSpins {
+    /// spins left to play without a bet transfer
+    pub remaining: u32,
+    /// lamports bet on the spin that won them, free spins pay for this bet
+    pub bet_amount: u64,
+    /// `RuleSetId` of the spin that won them, free spins play the same table
+    pub rule_set: u8,
+    /// `RuleSet::content_hash` of that table; if the table changed since, the free
+    /// spins are forfeited instead of played on other rules
+    pub rule_set_hash: u64,
+}
+
+impl FreeSpins {
+    pub const SEED: &'static [u8] = b"free_spins";
+    /// bincode size: u32 + u64 + u8 + u64
+    pub const LEN: usize = 4 + 8 + 1 + 8;
+
+    pub fn find_address(player: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, player.as_ref()], program_id)
+    }
+}
+
+/// Settings of the house, one account per program. Until it is initialized spins
+/// use `Config::default()`, the limits the program had before the config.
+/// seeds = [Config::SEED]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Config {
+    /// may withdraw from the bank and change the config
+    pub authority: Pubkey,
+    /// no paid spins while set
+    pub paused: bool,
+    /// smallest paid bet, in lamports
+    pub min_bet: u64,
+    /// largest paid bet, in lamports
+    pub max_bet: u64,
+    /// `RuleSetId` paid spins must play, or `ACCOUNT_RULE_SET`; free spins keep the
+    /// one they were won on
+    pub rule_set: u8,
+    /// basis points of a paid bet the player pays on top of it, into the bank
+    pub house_fee_bps: u16,
+    /// `SetRuleSet` refuses rule sets that may return more than this, in basis points
+    pub max_rtp_bps: u16,
+}
+
+impl Config {
+    pub const SEED: &'static [u8] = b"config";
+    /// bincode size: pubkey + bool + u64 + u64 + u8 + u16 + u16
+    pub const LEN: usize = 32 + 1 + 8 + 8 + 1 + 2 + 2;
+    /// `min_bet` can't go under this, a bet must dwarf the transaction fees
+    pub const MIN_BET_FLOOR: u64 = 33 * 5000;
+    /// `max_bet` can't go over this, bets are `1 << bet_exp` with `bet_exp < 63`
+    pub const MAX_BET_CEILING: u64 = 1 << 62;
+    /// 10%
+    pub const MAX_HOUSE_FEE_BPS: u16 = 1000;
+    /// `rule_set` of the table in the `RuleSetAccount`, no `RuleSetId` uses it. Set
+    /// with `UpdateConfig` after `SetRuleSet` uploaded the table
+    pub const ACCOUNT_RULE_SET: u8 = 0;
+
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+
+    /// lamports the player pays on top of `bet`
+    pub fn house_fee(&self, bet: u64) -> u64 {
+        bet / 10000 * self.house_fee_bps as u64
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            authority: Pubkey::default(),
+            paused: false,
+            min_bet: Self::MIN_BET_FLOOR + 1,
+            max_bet: Self::MAX_BET_CEILING,
+            rule_set: crate::RuleSetId::DEFAULT as u8,
+            house_fee_bps: 0,
+            max_rtp_bps: 9700,
+        }
+    }
+}
+
+/// The rule set uploaded by the authority, played when `Config::rule_set` is
+/// `Config::ACCOUNT_RULE_SET`. The data is the `RuleSet::serialize` file, as long as it.
+/// seeds = [RuleSetAccount::SEED]
+pub struct RuleSetAccount;
+
+impl RuleSetAccount {
+    pub const SEED: &'static [u8] = b"rule_set";
+
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
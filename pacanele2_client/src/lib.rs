@@ -1,12 +1,18 @@
 use base64::Engine;
 use solana_sdk::compute_budget::ComputeBudgetInstruction;
 use solana_sdk::native_token::LAMPORTS_PER_SOL;
-use solana_sdk::transaction::VersionedTransaction;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
 use solana_sdk::transaction_context::TransactionReturnData;
 use wasm_client_solana::solana_transaction_status::UiTransactionReturnData;
 use wasm_client_solana::solana_transaction_status::UiTransactionStatusMeta;
 
 pub use solana_sdk::account::Account;
+pub use pacanele2::error::PacaneleError;
+pub use pacanele2::instruction::PacaneleInstruction;
+pub use pacanele2::state::{Config, RuleSetAccount};
+pub use pacanele2::RuleSetId;
+pub use pacanele2::RuleSet;
 
 pub use wasm_client_solana::ClientResult;
 use wasm_client_solana::RpcSimulateTransactionConfig;
@@ -42,6 +48,74 @@ pub fn get_bank_address() -> (Pubkey, u8) {
     Pubkey::find_program_address(&[seed], &program_id)
 }
 
+pub fn get_jackpot_address() -> (Pubkey, u8) {
+    let program_id = get_program_address();
+    let seed = b"jackpot";
+    Pubkey::find_program_address(&[seed], &program_id)
+}
+
+pub fn get_config_address() -> (Pubkey, u8) {
+    Config::find_address(&get_program_address())
+}
+
+/// The account, `None` only if it does not exist; RPC failures are errors named by `what`.
+async fn get_account_if_exists(client: &RpcClient, address: &Pubkey, what: &str) -> Result<Option<Account>, String> {
+    client
+        .get_account_with_commitment(address, client.commitment_config())
+        .await
+        .map_err(|e| format!("{} account: {}", what, e))
+}
+
+/// house config, the defaults the program uses if it was not initialized yet
+pub async fn get_config(client: &RpcClient) -> Result<Config, String> {
+    let Some(acc) = get_account_if_exists(client, &get_config_address().0, "config").await? else {
+        return Ok(Config::default());
+    };
+    bincode::deserialize(&acc.data).map_err(|e| format!("config decode error: {}", e))
+}
+
+pub fn get_rule_set_address() -> (Pubkey, u8) {
+    RuleSetAccount::find_address(&get_program_address())
+}
+
+/// The `rule_set` paid spins must send, and the rules they play: a `RuleSetId` or the
+/// rule set the authority uploaded.
+pub async fn get_table(client: &RpcClient) -> Result<(u8, RuleSet), String> {
+    let config = get_config(client).await?;
+    if let Some(id) = RuleSetId::from_u8(config.rule_set) {
+        return Ok((config.rule_set, id.rule_set()));
+    }
+    let acc = client
+        .get_account(&get_rule_set_address().0)
+        .await
+        .map_err(|e| format!("rule set account: {}", e))?;
+    let rule_set = RuleSet::deserialize(&acc.data).map_err(|e| format!("{}", e))?;
+    Ok((config.rule_set, rule_set))
+}
+
+/// lamports in the jackpot pool with its rent, 0 before the config was initialized
+pub async fn get_jackpot_lamports(client: &RpcClient) -> Result<u64, String> {
+    let Some(acc) = get_account_if_exists(client, &get_jackpot_address().0, "jackpot").await? else {
+        return Ok(0);
+    };
+    Ok(acc.lamports)
+}
+
+pub fn get_free_spins_address(player: &Pubkey) -> (Pubkey, u8) {
+    pacanele2::state::FreeSpins::find_address(player, &get_program_address())
+}
+
+/// free spins left for the player, 0 if the player never played
+pub async fn get_free_spins(client: &RpcClient, player: &Pubkey) -> Result<u32, String> {
+    let address = get_free_spins_address(player).0;
+    let Some(acc) = get_account_if_exists(client, &address, "free spins").await? else {
+        return Ok(0);
+    };
+    let free_spins: pacanele2::state::FreeSpins =
+        bincode::deserialize(&acc.data).map_err(|e| format!("free spins decode error: {}", e))?;
+    Ok(free_spins.remaining)
+}
+
 pub fn get_solana_rpc_url() -> String {
     String::from("https://api.devnet.solana.com")
     // String::from("http://127.0.0.1:8899")
@@ -91,6 +165,39 @@ pub async fn get_tx_meta(client: &RpcClient, signature: &Signature) -> Result<Ui
     Ok(transaction.transaction.meta.unwrap())
 }
 
+/// The program error behind a failed transaction, `None` if it failed for another reason.
+pub fn decode_program_error(err: &TransactionError) -> Option<PacaneleError> {
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => PacaneleError::from_code(*code),
+        _ => None,
+    }
+}
+
+/// Readable reason for a failed transaction, "bank too poor for this bet" rather than "custom program error: 0xc".
+pub fn describe_transaction_error(err: &TransactionError) -> String {
+    match decode_program_error(err) {
+        Some(e) => e.to_string(),
+        None => err.to_string(),
+    }
+}
+
+/// Readable reason for a failed send. The RPC only hands back its message, with the
+/// program error as "custom program error: 0x..." in it.
+pub fn describe_client_error(err: &wasm_client_solana::ClientError) -> String {
+    let text = err.to_string();
+    let code = text
+        .split("custom program error: 0x")
+        .nth(1)
+        .and_then(|rest| {
+            let hex: String = rest.chars().take_while(|c| c.is_ascii_hexdigit()).collect();
+            u32::from_str_radix(&hex, 16).ok()
+        });
+    match code.and_then(PacaneleError::from_code) {
+        Some(e) => e.to_string(),
+        None => text,
+    }
+}
+
 pub async fn simulate_compute_limit(
     client: &RpcClient,
     instructions: &[Instruction],
@@ -102,6 +209,10 @@ pub async fn simulate_compute_limit(
         .simulate_transaction(&vt)
         .await
         .map_err(|e| format!("! sim fail: {:?}", e))?;
+    // the real transaction would fail the same way, say why instead of sending it
+    if let Some(err) = sim.value.err {
+        return Err(describe_transaction_error(&err));
+    }
     let consumed = sim.value.units_consumed.unwrap_or_default();
     let consumed = (consumed + 3000 + (consumed / 2)).clamp(5000, 166666);
     Ok(consumed as u32)
@@ -148,96 +259,224 @@ pub async fn run_transaction(
     let signature = match client.send_and_confirm_transaction(&vt).await {
         Ok(signature) => signature,
         Err(err) => {
-            return Err(format!("! Error sending transaction:\n {}", err));
+            return Err(format!(
+                "! Error sending transaction:\n {}",
+                describe_client_error(&err)
+            ));
         }
     };
 
-    Ok(get_tx_meta(&client, &signature).await?)
+    let meta = get_tx_meta(client, &signature).await?;
+    // the state changed between the simulation and the real run
+    if let Some(err) = &meta.err {
+        return Err(describe_transaction_error(err));
+    }
+    Ok(meta)
 }
 
 pub async fn _demo() -> Result<UiTransactionStatusMeta, String> {
     let client = get_client().await;
     let k = create_new_keypair();
     request_airdrop(&client, &k.pubkey(), 1).await;
-    spin_pcnl(&client, k, 20).await
+    spin_pcnl(&client, k, 20, RuleSetId::DEFAULT as u8).await
 }
 
-fn spin_pcnl_instruction(player: &Pubkey, bet_amount_exp: u8) -> Result<Instruction, String> {
+/// `rule_set` is a `RuleSetId` or `Config::ACCOUNT_RULE_SET`, see `get_table`
+pub fn spin_pcnl_instruction(player: &Pubkey, bet_amount_exp: u8, rule_set: u8) -> Result<Instruction, String> {
     assert!(bet_amount_exp > 10);
     assert!(bet_amount_exp < 62);
 
     let program_id = get_program_address();
     let (bank_address, bank_bump) = get_bank_address();
+    let (free_spins_address, free_spins_bump) = get_free_spins_address(player);
+    let (jackpot_address, jackpot_bump) = get_jackpot_address();
+    let (config_address, config_bump) = get_config_address();
+    let (rule_set_address, rule_set_bump) = get_rule_set_address();
 
-    let instruction_spin_pcnl = Instruction::new_with_bytes(
+    let instruction_spin_pcnl = Instruction::new_with_bincode(
         program_id,
-        &[
-            // instruction data 1 = bank account bump (for seed)
-            bank_bump, 
-            // instruction data 2 = bet amount (as 2's exponent)
-            bet_amount_exp
-        ],
+        &PacaneleInstruction::Spin {
+            bank_bump,
+            bet_exp: bet_amount_exp,
+            free_spins_bump,
+            jackpot_bump,
+            config_bump,
+            rule_set,
+            rule_set_bump,
+        },
         // account data
         vec![
             // 0 account = instructions sysvar (read program id)
-            AccountMeta {
-                pubkey: solana_sdk::sysvar::instructions::id(),
-                is_signer: false,
-                is_writable: false,
-            },
+            AccountMeta::new_readonly(solana_sdk::sysvar::instructions::id(), false),
             // 1  account = slot_hashes metavar for some bytes
-            AccountMeta {
-                pubkey: solana_sdk::slot_hashes::sysvar::id(),
-                is_signer: false,
-                is_writable: false,
-            },
+            AccountMeta::new_readonly(solana_sdk::slot_hashes::sysvar::id(), false),
             // 2 account = system program
-            AccountMeta {
-                pubkey: solana_sdk::system_program::id(),
-                is_signer: false,
-                is_writable: false,
-            },
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
             // 3 account = bank
-            AccountMeta {
-                pubkey: bank_address,
-                is_signer: false,
-                is_writable: true,
-            },
+            AccountMeta::new(bank_address, false),
             // 4 account = player
-            AccountMeta {
-                pubkey: *player,
-                is_signer: true,
-                is_writable: true,
-            },
+            AccountMeta::new(*player, true),
             // 5 account = program id
-            AccountMeta {
-                pubkey: program_id,
-                is_signer: false,
-                is_writable: true,
-            },
+            AccountMeta::new(program_id, false),
+            // 6 account = player free spins
+            AccountMeta::new(free_spins_address, false),
+            // 7 account = jackpot
+            AccountMeta::new(jackpot_address, false),
+            // 8 account = config (paused?)
+            AccountMeta::new_readonly(config_address, false),
+            // 9 account = uploaded rule set
+            AccountMeta::new_readonly(rule_set_address, false),
         ],
     );
     Ok(instruction_spin_pcnl)
 }
 
+/// only the upgrade authority of the program can send it, once
+pub fn initialize_config_instruction(upgrade_authority: &Pubkey, authority: &Pubkey) -> Instruction {
+    let program_id = get_program_address();
+    Instruction::new_with_bincode(
+        program_id,
+        &PacaneleInstruction::InitializeConfig { authority: *authority },
+        vec![
+            AccountMeta::new(*upgrade_authority, true),
+            AccountMeta::new(get_config_address().0, false),
+            AccountMeta::new_readonly(
+                solana_sdk::bpf_loader_upgradeable::get_program_data_address(&program_id),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new(get_jackpot_address().0, false),
+        ],
+    )
+}
+
+pub fn fund_bank_instruction(funder: &Pubkey, lamports: u64) -> Instruction {
+    Instruction::new_with_bincode(
+        get_program_address(),
+        &PacaneleInstruction::FundBank { lamports },
+        vec![
+            AccountMeta::new(*funder, true),
+            AccountMeta::new(get_bank_address().0, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+/// the lamports go to the config authority
+pub fn withdraw_from_bank_instruction(authority: &Pubkey, lamports: u64) -> Instruction {
+    Instruction::new_with_bincode(
+        get_program_address(),
+        &PacaneleInstruction::WithdrawFromBank { lamports },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(get_config_address().0, false),
+            AccountMeta::new(get_bank_address().0, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+pub fn set_paused_instruction(authority: &Pubkey, paused: bool) -> Instruction {
+    Instruction::new_with_bincode(
+        get_program_address(),
+        &PacaneleInstruction::SetPaused { paused },
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(get_config_address().0, false),
+        ],
+    )
+}
+
+pub fn update_config_instruction(
+    authority: &Pubkey,
+    min_bet: u64,
+    max_bet: u64,
+    rule_set: u8,
+    house_fee_bps: u16,
+    max_rtp_bps: u16,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        get_program_address(),
+        &PacaneleInstruction::UpdateConfig { min_bet, max_bet, rule_set, house_fee_bps, max_rtp_bps },
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(get_config_address().0, false),
+            AccountMeta::new_readonly(get_rule_set_address().0, false),
+        ],
+    )
+}
+
+/// the serialized rule set must fit in the transaction with the accounts; paid
+/// spins play it after `update_config` with `Config::ACCOUNT_RULE_SET`
+pub fn set_rule_set_instruction(authority: &Pubkey, rule_set: &RuleSet) -> Instruction {
+    Instruction::new_with_bincode(
+        get_program_address(),
+        &PacaneleInstruction::SetRuleSet { rule_set: rule_set.serialize() },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(get_config_address().0, false),
+            AccountMeta::new(get_rule_set_address().0, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    )
+}
+
+pub async fn initialize_config(client: &RpcClient, upgrade_authority: Keypair, authority: &Pubkey) -> Result<UiTransactionStatusMeta, String> {
+    let instruction = initialize_config_instruction(&upgrade_authority.pubkey(), authority);
+    run_transaction(client, upgrade_authority, &[instruction]).await
+}
+
+pub async fn fund_bank(client: &RpcClient, funder: Keypair, lamports: u64) -> Result<UiTransactionStatusMeta, String> {
+    let instruction = fund_bank_instruction(&funder.pubkey(), lamports);
+    run_transaction(client, funder, &[instruction]).await
+}
+
+pub async fn withdraw_from_bank(client: &RpcClient, authority: Keypair, lamports: u64) -> Result<UiTransactionStatusMeta, String> {
+    let instruction = withdraw_from_bank_instruction(&authority.pubkey(), lamports);
+    run_transaction(client, authority, &[instruction]).await
+}
+
+pub async fn set_paused(client: &RpcClient, authority: Keypair, paused: bool) -> Result<UiTransactionStatusMeta, String> {
+    let instruction = set_paused_instruction(&authority.pubkey(), paused);
+    run_transaction(client, authority, &[instruction]).await
+}
+
+pub async fn update_config(
+    client: &RpcClient,
+    authority: Keypair,
+    min_bet: u64,
+    max_bet: u64,
+    rule_set: u8,
+    house_fee_bps: u16,
+    max_rtp_bps: u16,
+) -> Result<UiTransactionStatusMeta, String> {
+    let instruction = update_config_instruction(&authority.pubkey(), min_bet, max_bet, rule_set, house_fee_bps, max_rtp_bps);
+    run_transaction(client, authority, &[instruction]).await
+}
+
+pub async fn set_rule_set(client: &RpcClient, authority: Keypair, rule_set: &RuleSet) -> Result<UiTransactionStatusMeta, String> {
+    let instruction = set_rule_set_instruction(&authority.pubkey(), rule_set);
+    run_transaction(client, authority, &[instruction]).await
+}
+
 pub async fn spin_pcnl(
     client: &RpcClient,
     payer: Keypair,
     bet_amount_exp: u8,
+    rule_set: u8,
 ) -> Result<UiTransactionStatusMeta, String> {
     let bet_interval = pcnl_possible_bet_interval(client, &payer.pubkey()).await?;
-    info!("spin_pcnl {bet_amount_exp} {bet_interval:?}");
+    info!("spin_pcnl {bet_amount_exp} {bet_interval:?} {rule_set}");
     if bet_amount_exp > bet_interval.1 || bet_amount_exp < bet_interval.0 {
         return Err(format!("bet amount exp {bet_amount_exp} not in interval {bet_interval:?} inclusive!"))
     }
 
-    let instruction_spin_pcnl = spin_pcnl_instruction(&payer.pubkey(), bet_amount_exp)?;
+    let instruction_spin_pcnl = spin_pcnl_instruction(&payer.pubkey(), bet_amount_exp, rule_set)?;
     run_transaction(&client, payer, &[instruction_spin_pcnl]).await
 }
 
 pub async fn pcnl_possible_bet_interval(client: &RpcClient, key: &Pubkey) -> Result<(u8, u8), String> {
-    // 1 / MIN_BET_PER_FEE must be smaller than 1-payout
-    const MIN_BET_PER_FEE : u64 = 66;
+    use pacanele2::bank::MIN_BET_PER_FEE;
     const SOLANA_BASE_FEE : u64 = 5000;
     let acc =client.get_account(key).await.map_err(|e| format!("{}", e))?;
     let balance = acc.lamports;
@@ -247,15 +486,28 @@ pub async fn pcnl_possible_bet_interval(client: &RpcClient, key: &Pubkey) -> Res
 
 
     let rent: u64 =  client.get_minimum_balance_for_rent_exemption(1).await.map_err(|e| format!("{}", e))?;
+    // the first spin also pays the rent of the player free spins account
+    let free_spins_rent: u64 = if get_account_if_exists(client, &get_free_spins_address(key).0, "free spins").await?.is_some() {
+        0
+    } else {
+        client.get_minimum_balance_for_rent_exemption(pacanele2::state::FreeSpins::LEN).await.map_err(|e| format!("{}", e))?
+    };
+
+    // the house limits, as exponents: ceil(log2(min_bet)) ..= floor(log2(max_bet))
+    let config = get_config(client).await?;
+    let config_min_exp = (u64::BITS - (config.min_bet - 1).leading_zeros()) as u8;
+    let config_max_exp = config.max_bet.ilog2() as u8;
 
-    let _min_bet_exp = f64::log2(2.0*(MIN_BET_PER_FEE * SOLANA_BASE_FEE + 1) as f64) as u8;
-    let instruction_spin_pcnl = spin_pcnl_instruction(key, _min_bet_exp)?;
+    let _min_bet_exp = (f64::log2(2.0*(MIN_BET_PER_FEE * SOLANA_BASE_FEE + 1) as f64) as u8).max(config_min_exp);
+    let instruction_spin_pcnl = spin_pcnl_instruction(key, _min_bet_exp, config.rule_set)?;
     let simulated_compute_unit = simulate_compute_limit(client, &[instruction_spin_pcnl], key).await?;
     let simulated_price = avg_priority_fee(client).await?;
     let exact_tx_price = simulated_compute_unit as u64 * simulated_price / 1000000 + SOLANA_BASE_FEE;
     let exact_tx_price = exact_tx_price + exact_tx_price / 10;
 
-    let available_to_play = (balance as i64 - rent as i64 - exact_tx_price as i64 - SOLANA_BASE_FEE as i64).max(0) as u64;
+    let available_to_play = (balance as i64 - rent as i64 - free_spins_rent as i64 - exact_tx_price as i64 - SOLANA_BASE_FEE as i64).max(0) as u64;
+    // the house fee is paid on top of the bet
+    let available_to_play = (available_to_play as u128 * 10000 / (10000 + config.house_fee_bps as u128)) as u64;
 
     let bank_available = (bank_balance as i64 - rent as i64 - exact_tx_price as i64 - SOLANA_BASE_FEE as i64).max(0) as u64;
 
@@ -271,9 +523,9 @@ pub async fn pcnl_possible_bet_interval(client: &RpcClient, key: &Pubkey) -> Res
         info!("{}", msg);
         return Err(msg);
     }
-    let min_bet = f64::log2((exact_tx_price * MIN_BET_PER_FEE - 1) as f64) as u8;
-    let max_bet =  f64::log2((available_to_play - 1) as f64) as u8;
-    let bank_bet = f64::log2(((bank_available - 1)/MIN_BET_PER_FEE) as f64) as u8;
+    let min_bet = (f64::log2((exact_tx_price * MIN_BET_PER_FEE - 1) as f64) as u8).max(config_min_exp);
+    let max_bet = (f64::log2((available_to_play - 1) as f64) as u8).min(config_max_exp);
+    let bank_bet = pacanele2::bank::max_bet_exp(bank_available).unwrap_or(0);
 
     if  (min_bet > 10) && (max_bet > 10) && (bank_bet > 10) && (min_bet <= max_bet) && (max_bet < 62) && (bank_bet < 62) && (min_bet <= bank_bet) {
 
@@ -2,17 +2,34 @@ use std::env;
 use std::fs;
 use std::path::Path;
 
+/// the frontend `assets` dir, every symbol `asset` is relative to it
+const ASSETS_DIR: &str = "../frontend/web_pacanele/assets";
+
+#[derive(serde::Deserialize)]
+struct Manifest {
+    fruit: Vec<Symbol>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Symbol {
+    name: String,
+    display: String,
+    tier: String,
+    asset: String,
+    colour: String,
+    role: Option<String>,
+}
+
 fn main() {
-    let fruits: Vec<_> = include_str!("src/fruits.txt")
-        .trim()
-        .split_whitespace()
-        .map(|s| s.trim().to_string())
-        .collect();
+    let manifest: Manifest = toml::from_str(include_str!("src/fruits.toml"))
+        .unwrap_or_else(|e| panic!("src/fruits.toml: {e}"));
+    let fruits = manifest.fruit;
+    check_symbols(&fruits);
 
     let out_dir = env::var_os("OUT_DIR").unwrap();
     let dest_path = Path::new(&out_dir).join("get_all_fruits.rs");
 
-    // let get_all_fruits = fn_get_all_fruits(&fruits);
     let enum_fruit = write_enum_fruit(&fruits);
 
     let all_code = format!("\n{enum_fruit}");
@@ -20,23 +37,91 @@ fn main() {
     fs::write(&dest_path, &all_code).unwrap();
 
     println!("cargo::rerun-if-changed=build.rs");
-    println!("cargo::rerun-if-changed=src/fruits.txt");
+    println!("cargo::rerun-if-changed=src/fruits.toml");
+    println!("cargo::rerun-if-changed={ASSETS_DIR}");
+}
+
+/// Panics on anything the generated accessors could not represent, or a missing asset.
+fn check_symbols(fruits: &[Symbol]) {
+    assert!(fruits.len() >= 3, "src/fruits.toml: need at least 3 fruits");
+    let mut names = std::collections::BTreeSet::new();
+    for s in fruits {
+        let name = &s.name;
+        assert!(
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'),
+            "src/fruits.toml: bad fruit name {name:?}"
+        );
+        assert!(
+            names.insert(name),
+            "src/fruits.toml: {name} is listed twice"
+        );
+        assert!(
+            ["low", "mid", "high", "special"].contains(&s.tier.as_str()),
+            "src/fruits.toml: {name} has tier {:?}, use low, mid, high or special",
+            s.tier
+        );
+        if let Some(role) = &s.role {
+            assert!(
+                ["wild", "scatter", "jackpot"].contains(&role.as_str()),
+                "src/fruits.toml: {name} has role {role:?}, use wild, scatter or jackpot"
+            );
+        }
+        let hex = s.colour.strip_prefix('#').unwrap_or("");
+        assert!(
+            hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()),
+            "src/fruits.toml: {name} has colour {:?}, use #rrggbb",
+            s.colour
+        );
+        let asset = Path::new(ASSETS_DIR).join(&s.asset);
+        assert!(
+            asset.is_file(),
+            "src/fruits.toml: asset of {name} is missing: {}",
+            asset.display()
+        );
+    }
 }
 
-fn write_enum_fruit(fruits: &[String]) -> String {
+fn write_enum_fruit(fruits: &[Symbol]) -> String {
     let modified_fruit = fruits
         .iter()
-        .map(|x| x.replace("-", "_"))
+        .map(|x| x.name.replace("-", "_"))
         .collect::<Vec<_>>();
 
     let mut variants_txt = "".to_string();
     let mut match_txt = "".to_string();
+    let mut display_txt = "".to_string();
+    let mut tier_txt = "".to_string();
+    let mut asset_txt = "".to_string();
+    let mut colour_txt = "".to_string();
+    let mut role_txt = "".to_string();
     for (i, (fruit, modified_fruit)) in fruits.iter().zip(modified_fruit.iter()).enumerate() {
         let line = format!("{modified_fruit} = {i},\n");
         variants_txt.push_str(&line);
 
-        let line = format!("Self::{modified_fruit} => \"{fruit}\",\n");
+        let line = format!("Self::{modified_fruit} => \"{}\",\n", fruit.name);
         match_txt.push_str(&line);
+
+        let line = format!("Self::{modified_fruit} => {:?},\n", fruit.display);
+        display_txt.push_str(&line);
+
+        let line = format!(
+            "Self::{modified_fruit} => crate::symbol::SymbolTier::{},\n",
+            capitalize(&fruit.tier)
+        );
+        tier_txt.push_str(&line);
+
+        let line = format!("Self::{modified_fruit} => {:?},\n", fruit.asset);
+        asset_txt.push_str(&line);
+
+        let line = format!("Self::{modified_fruit} => {:?},\n", fruit.colour);
+        colour_txt.push_str(&line);
+
+        let role = match &fruit.role {
+            Some(role) => format!("Some(crate::symbol::SymbolRole::{})", capitalize(role)),
+            None => "None".to_string(),
+        };
+        let line = format!("Self::{modified_fruit} => {role},\n");
+        role_txt.push_str(&line);
     }
     format!(
         "
@@ -49,6 +134,8 @@ fn write_enum_fruit(fruits: &[String]) -> String {
         strum_macros::EnumCount, 
         strum_macros::EnumIter, 
         strum::VariantArray,
+        strum_macros::EnumString,
+        strum_macros::Display,
     )]
     #[repr(u8)]
     pub enum Fruit {{
@@ -60,21 +147,43 @@ fn write_enum_fruit(fruits: &[String]) -> String {
                 {match_txt}
             }}
         }}
+        /// name shown to players
+        pub fn display_name(&self) -> &'static str {{
+            match self {{
+                {display_txt}
+            }}
+        }}
+        pub fn tier(&self) -> crate::symbol::SymbolTier {{
+            match self {{
+                {tier_txt}
+            }}
+        }}
+        /// image, relative to the frontend `assets` dir
+        pub fn asset_path(&self) -> &'static str {{
+            match self {{
+                {asset_txt}
+            }}
+        }}
+        /// `#rrggbb`
+        pub fn colour(&self) -> &'static str {{
+            match self {{
+                {colour_txt}
+            }}
+        }}
+        pub fn role(&self) -> Option<crate::symbol::SymbolRole> {{
+            match self {{
+                {role_txt}
+            }}
+        }}
     }}
     "
     )
 }
 
-fn fn_get_all_fruits(fruits: &[String]) -> String {
-    let fruit_count = fruits.len();
-    let inner_str = fruits
-        .into_iter()
-        .map(|x| format!("\"{x}\""))
-        .collect::<Vec<String>>()
-        .join(",\n ");
-
-    let part_a = format!("pub fn get_all_fruits() -> [&'static str;  {fruit_count}] {{");
-    let part_b = format!("[{inner_str}]");
-    let part_c = "}";
-    format!("{part_a}{part_b}{part_c}")
+fn capitalize(s: &str) -> String {
+    let mut c = s.chars();
+    match c.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + c.as_str(),
+        None => String::new(),
+    }
 }
@@ -1,6 +1,10 @@
 #!/bin/bash
+# usage: ./generate.sh NAME [generate flags]
+# writes the canonical rule_sets/NAME.toml, then everything built from it
 set -ex
 cd "$(dirname "${BASH_SOURCE[0]}")"
 
-cargo run --bin generate --features generate
-cargo fmt
+name="${1:?usage: ./generate.sh NAME [generate flags]}"
+shift
+cargo run --bin rules --all-features -- generate --out "rule_sets/$name.toml" "$@"
+cargo run --bin rules --all-features -- publish "rule_sets/$name.toml" "$name"
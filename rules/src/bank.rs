@@ -0,0 +1,48 @@
+//! How the house bank limits bets and payouts. The program and the client call
+//! these, `solvency` simulates them.
+
+/// The client only offers bets up to the free bank balance divided by this.
+/// 1 / MIN_BET_PER_FEE must be smaller than 1 - rtp.
+pub const MIN_BET_PER_FEE: u64 = 66;
+/// rent exemption of one byte of account data, in lamports
+pub const RENT_PER_BYTE: u64 = 6960;
+/// rent exemption of an empty account, its 128 bytes of metadata, in lamports
+pub const EMPTY_ACCOUNT_RENT: u64 = 128 * RENT_PER_BYTE;
+/// the program keeps this much in the bank whatever it pays out
+pub const BANK_RESERVE: u64 = 2 * EMPTY_ACCOUNT_RENT;
+
+/// Most the program pays for one spin from a bank holding `bank` lamports: half of
+/// it, less the reserve. The rule set `max_win` is applied before this.
+pub const fn max_payout(bank: u64) -> u64 {
+    (bank / 2).saturating_sub(BANK_RESERVE)
+}
+
+/// Largest bet exponent the client offers against `bank_available` lamports, the
+/// bank balance less its rent and the fees. `None` when not even `1 << 0` fits.
+pub const fn max_bet_exp(bank_available: u64) -> Option<u8> {
+    match bank_available.saturating_sub(1) / MIN_BET_PER_FEE {
+        0 => None,
+        bets => Some(bets.ilog2() as u8),
+    }
+}
+
+/// smallest bank for which the client offers `1 << bet_exp`
+pub const fn min_bank_for_bet(bet_exp: u8) -> u64 {
+    (MIN_BET_PER_FEE << bet_exp) + 1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bank_policy() {
+        assert_eq!(max_payout(0), 0);
+        assert_eq!(max_payout(10 * BANK_RESERVE), 4 * BANK_RESERVE);
+        assert_eq!(max_bet_exp(MIN_BET_PER_FEE), None);
+        for exp in [11, 20, 40] {
+            assert_eq!(max_bet_exp(min_bank_for_bet(exp)), Some(exp));
+            assert_eq!(max_bet_exp(min_bank_for_bet(exp) - 1), Some(exp - 1));
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Allocation free form of a rule set, for the on-chain program. `rust_constructor`
+//! emits it as a `const` next to the `RuleSet` constructor, so a spin is a few
+//! array lookups instead of building and walking `BTreeMap`s.
+
+use strum::EnumCount;
+
+use crate::rule_set::{AppliedCap, JackpotRule, MatchedRule, RuleSet, ScatterRule, SpinOutcome};
+use crate::Fruit;
+
+pub const FRUIT_COUNT: usize = Fruit::COUNT;
+
+/// Same rules as a `RuleSet`, flattened into lookup tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompiledRuleSet<'a> {
+    pub wheel_count: u8,
+    /// per wheel, `prob` summed up in `Fruit` order: a seed lands on the first
+    /// fruit whose entry is >= the seed, skipping fruits missing from the wheel
+    pub cdf: &'a [[u16; FRUIT_COUNT]],
+    /// `rewards[fruit as usize * (wheel_count + 1) + count]` in basis points, 0 = no reward
+    pub rewards: &'a [u32],
+    pub wilds: [bool; FRUIT_COUNT],
+    pub scatter: Option<ScatterRule>,
+    pub jackpot: Option<JackpotRule>,
+    pub max_win: u32,
+}
+
+/// Result of `CompiledRuleSet::play_from_seed`, see `SpinOutcome` for the meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompiledSpin {
+    /// how many times every fruit counts, after the wilds joined the best fruit
+    pub hits: [u8; FRUIT_COUNT],
+    pub multiplier: u8,
+    /// matched rewards times `multiplier`, before `max_win`
+    pub uncapped: u64,
+    pub reward: u32,
+    pub free_spins: u8,
+    pub jackpot: bool,
+}
+
+impl RuleSet {
+    /// `CompiledRuleSet::cdf` of this rule set
+    pub fn cdf_table(&self) -> Vec<[u16; FRUIT_COUNT]> {
+        self.reels()
+            .iter()
+            .map(|reel| {
+                let mut cdf = [0u16; FRUIT_COUNT];
+                let mut sum = 0u16;
+                for (i, fruit) in Fruit::all().iter().enumerate() {
+                    sum += reel.get(fruit).copied().unwrap_or(0);
+                    cdf[i] = sum;
+                }
+                cdf
+            })
+            .collect()
+    }
+
+    /// `CompiledRuleSet::rewards` of this rule set
+    pub fn reward_table(&self) -> Vec<u32> {
+        let stride = self.wheel_count as usize + 1;
+        let mut table = vec![0; FRUIT_COUNT * stride];
+        for ((fruit, count), reward) in self.rewards.iter() {
+            table[*fruit as usize * stride + *count as usize] = *reward;
+        }
+        table
+    }
+
+    /// the tables come from `cdf_table` and `reward_table`
+    pub fn compiled<'a>(
+        &self,
+        cdf: &'a [[u16; FRUIT_COUNT]],
+        rewards: &'a [u32],
+    ) -> CompiledRuleSet<'a> {
+        let mut wilds = [false; FRUIT_COUNT];
+        for fruit in self.wilds.iter() {
+            wilds[*fruit as usize] = true;
+        }
+        CompiledRuleSet {
+            wheel_count: self.wheel_count,
+            cdf,
+            rewards,
+            wilds,
+            scatter: self.scatter,
+            jackpot: self.jackpot,
+            max_win: self.max_win,
+        }
+    }
+}
+
+impl CompiledRuleSet<'_> {
+    fn reward(&self, fruit: usize, count: u8) -> u32 {
+        let stride = self.wheel_count as usize + 1;
+        if count as usize >= stride {
+            return 0;
+        }
+        self.rewards[fruit * stride + count as usize]
+    }
+
+    fn total(&self, hits: &[u8; FRUIT_COUNT]) -> u64 {
+        (0..FRUIT_COUNT)
+            .map(|f| self.reward(f, hits[f]) as u64)
+            .sum()
+    }
+
+    /// Same as `RuleSet::play_random_from_seed` (multiplier 1) or
+    /// `play_free_spin_from_seed` (the scatter multiplier). The fruits go to `symbols`,
+    /// both slices are `wheel_count` long.
+    pub fn play_from_seed(
+        &self,
+        random_seed: &[u16],
+        symbols: &mut [Fruit],
+        free_spin: bool,
+    ) -> CompiledSpin {
+        assert_eq!(random_seed.len(), self.wheel_count as usize);
+        assert_eq!(symbols.len(), self.wheel_count as usize);
+        let mut own = [0u8; FRUIT_COUNT];
+        for (wheel, seed) in random_seed.iter().enumerate() {
+            let cdf = &self.cdf[wheel];
+            // a fruit missing from the wheel adds nothing to the sum and never shows
+            // up, as in `get_random_index_per_density`
+            let mut below = 0;
+            let i = cdf
+                .iter()
+                .position(|c| {
+                    let hit = *c > below && seed <= c;
+                    below = *c;
+                    hit
+                })
+                .unwrap_or(0);
+            symbols[wheel] = Fruit::all()[i];
+            own[i] += 1;
+        }
+
+        // the wilds count as themselves, or all join the same other fruit, as in `matched_rewards`
+        let mut hits = own;
+        let wild_count: u8 = (0..FRUIT_COUNT)
+            .filter(|f| self.wilds[*f])
+            .map(|f| own[f])
+            .sum();
+        if wild_count > 0 {
+            let max_win = self.max_win as u64;
+            let mut best_total = self.total(&own).min(max_win);
+            for fruit in (0..FRUIT_COUNT).filter(|f| !self.wilds[*f]) {
+                let mut joined = own;
+                for f in (0..FRUIT_COUNT).filter(|f| self.wilds[*f]) {
+                    joined[f] = 0;
+                }
+                joined[fruit] += wild_count;
+                let total = self.total(&joined).min(max_win);
+                if total > best_total {
+                    hits = joined;
+                    best_total = total;
+                }
+            }
+        }
+
+        let multiplier = match self.scatter {
+            Some(s) if free_spin => s.win_multiplier,
+            _ => 1,
+        };
+        let uncapped = self.total(&hits) * multiplier as u64;
+        CompiledSpin {
+            hits,
+            multiplier,
+            uncapped,
+            reward: uncapped.min(self.max_win as u64) as u32,
+            free_spins: match self.scatter {
+                Some(s) if own[s.fruit as usize] >= s.min_count => s.free_spins,
+                _ => 0,
+            },
+            jackpot: match self.jackpot {
+                Some(j) => own[j.fruit as usize] >= j.count,
+                None => false,
+            },
+        }
+    }
+
+    /// The `SpinOutcome` the `RuleSet` would give, for logs and return data.
+    /// Allocates, unlike `play_from_seed`.
+    pub fn outcome(
+        &self,
+        random_seed: &[u16],
+        symbols: &[Fruit],
+        spin: &CompiledSpin,
+    ) -> SpinOutcome {
+        let matched = (0..FRUIT_COUNT)
+            .filter(|f| self.reward(*f, spin.hits[*f]) > 0)
+            .map(|f| MatchedRule {
+                fruit: Fruit::all()[f],
+                count: spin.hits[f],
+                reward: self.reward(f, spin.hits[f]),
+            })
+            .collect();
+        let mut caps = vec![];
+        if spin.uncapped > spin.reward as u64 {
+            caps.push(AppliedCap::MaxWin {
+                uncapped: spin.uncapped,
+                cap: self.max_win,
+            });
+        }
+        SpinOutcome {
+            symbols: symbols.to_vec(),
+            stops: random_seed.to_vec(),
+            matched,
+            multiplier: spin.multiplier,
+            reward: spin.reward,
+            free_spins: spin.free_spins,
+            jackpot: spin.jackpot,
+            caps,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "generate", feature = "constructors"))]
+mod test {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::generated_rules::p96::P96;
+    use crate::rule_set::{RewardShape, REWARD_ONE};
+
+    fn assert_same_spins(r: &RuleSet, c: &CompiledRuleSet, spins: usize) {
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let mut symbols = vec![Fruit::all()[0]; r.wheel_count() as usize];
+        // every wheel on the edges between fruits first, then random stops
+        let mut edges: Vec<u16> = c
+            .cdf
+            .iter()
+            .flatten()
+            .flat_map(|e| [*e, e.saturating_add(1)])
+            .collect();
+        edges.extend([0, 1, u16::MAX]);
+        let edge_seeds = edges.iter().map(|e| vec![*e; r.wheel_count() as usize]);
+        let random_seeds = (0..spins).map(|_| {
+            (0..r.wheel_count())
+                .map(|_| rng.gen())
+                .collect::<Vec<u16>>()
+        });
+        for (i, seed) in edge_seeds.chain(random_seeds).enumerate() {
+            let free_spin = i % 2 == 1;
+            let spin = c.play_from_seed(&seed, &mut symbols, free_spin);
+            let expected = if free_spin {
+                r.play_free_spin_from_seed(&seed)
+            } else {
+                r.play_random_from_seed(&seed)
+            };
+            assert_eq!(c.outcome(&seed, &symbols, &spin), expected, "{seed:?}");
+        }
+    }
+
+    #[test]
+    fn test_p96_const() {
+        let r = RuleSet::p96();
+        let (cdf, rewards) = (r.cdf_table(), r.reward_table());
+        assert_eq!(P96, r.compiled(&cdf, &rewards));
+        assert_same_spins(&r, &P96, 20000);
+    }
+
+    #[test]
+    fn test_same_as_rule_set() {
+        let mut r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(15),
+            0.9,
+            4,
+            &[Fruit::dollars, Fruit::kiwi],
+            &RewardShape::default(),
+        );
+        let mut last = r.prob();
+        let sevens = last.remove(&Fruit::seven).unwrap();
+        *last.get_mut(&Fruit::bananas).unwrap() += sevens;
+        // no bananas, the first fruit, on the third wheel
+        let mut third = r.prob();
+        let bananas = third.remove(&Fruit::bananas).unwrap();
+        *third.get_mut(&Fruit::bell).unwrap() += bananas;
+        r.set_reel_prob(vec![r.prob(), r.prob(), third, last]);
+        r.set_scatter(Some(ScatterRule {
+            fruit: Fruit::bell,
+            min_count: 2,
+            free_spins: 3,
+            win_multiplier: 3,
+        }));
+        r.set_jackpot(Some(JackpotRule {
+            fruit: Fruit::seven,
+            count: 2,
+            contribution_bps: 100,
+        }));
+        r.set_max_win(r.rewards().values().copied().max().unwrap().max(REWARD_ONE));
+
+        let (cdf, rewards) = (r.cdf_table(), r.reward_table());
+        let c = r.compiled(&cdf, &rewards);
+        assert_same_spins(&r, &c, 50000);
+    }
+}
@@ -0,0 +1,188 @@
+//! Binary rule set file:
+//!
+//! | bytes | content |
+//! |---|---|
+//! | 4 | `MAGIC` |
+//! | 2 | format version, little endian |
+//! | 8 | `content_hash` of everything after it, little endian |
+//! | 1 | number of fruits |
+//! | 1 + len, per fruit | fruit name, in `Fruit` order |
+//! | rest | bincode `RuleSet` |
+
+use crate::error::RuleSetError;
+use crate::rule_set::RuleSet;
+use crate::Fruit;
+
+pub const MAGIC: [u8; 4] = *b"PCNL";
+/// 2: rewards in basis points of the bet, 3: `max_win`
+pub const FORMAT_VERSION: u16 = 3;
+const HEADER_LEN: usize = 4 + 2 + 8;
+
+/// 64 bit FNV-1a, catches truncated and edited files.
+pub const fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+/// The `content_hash` in the header of a `serialize` file, without checking the
+/// rest. Only trust it for a file that `RuleSet::deserialize` loaded before.
+pub fn header_content_hash(v: &[u8]) -> Option<u64> {
+    if v.len() < HEADER_LEN || v[0..4] != MAGIC {
+        return None;
+    }
+    Some(u64::from_le_bytes(v[6..HEADER_LEN].try_into().unwrap()))
+}
+
+/// names of the fruits in this build, as stored in the file
+pub fn symbol_names() -> Vec<String> {
+    Fruit::all().iter().map(|f| f.to_string()).collect()
+}
+
+impl RuleSet {
+    /// everything after the header
+    fn body(&self) -> Vec<u8> {
+        let mut body = vec![];
+        let symbols = symbol_names();
+        body.push(symbols.len() as u8);
+        for name in symbols {
+            body.push(name.len() as u8);
+            body.extend_from_slice(name.as_bytes());
+        }
+        body.extend(bincode::serialize(self).unwrap());
+        body
+    }
+
+    /// the hash in the file header, it changes with any rule or with the fruit list
+    pub fn content_hash(&self) -> u64 {
+        content_hash(&self.body())
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let body = self.body();
+        let mut v = Vec::with_capacity(HEADER_LEN + body.len());
+        v.extend_from_slice(&MAGIC);
+        v.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        v.extend_from_slice(&content_hash(&body).to_le_bytes());
+        v.extend(body);
+        v
+    }
+
+    /// Load a file written by `serialize`; the rule set is validated before it is returned.
+    pub fn deserialize(v: &[u8]) -> Result<Self, RuleSetError> {
+        if v.len() < HEADER_LEN {
+            return Err(if v.starts_with(&MAGIC) {
+                RuleSetError::Truncated
+            } else {
+                RuleSetError::BadMagic
+            });
+        }
+        if v[0..4] != MAGIC {
+            return Err(RuleSetError::BadMagic);
+        }
+        let version = u16::from_le_bytes([v[4], v[5]]);
+        if version != FORMAT_VERSION {
+            return Err(RuleSetError::UnsupportedVersion(version));
+        }
+        let stored = u64::from_le_bytes(v[6..HEADER_LEN].try_into().unwrap());
+        let body = &v[HEADER_LEN..];
+        let computed = content_hash(body);
+        if stored != computed {
+            return Err(RuleSetError::HashMismatch { stored, computed });
+        }
+
+        let (&symbol_count, mut rest) = body.split_first().ok_or(RuleSetError::Truncated)?;
+        let mut stored_symbols = vec![];
+        for _ in 0..symbol_count {
+            let (&len, tail) = rest.split_first().ok_or(RuleSetError::Truncated)?;
+            if tail.len() < len as usize {
+                return Err(RuleSetError::Truncated);
+            }
+            let (name, tail) = tail.split_at(len as usize);
+            stored_symbols.push(String::from_utf8_lossy(name).into_owned());
+            rest = tail;
+        }
+        let built = symbol_names();
+        if stored_symbols != built {
+            return Err(RuleSetError::SymbolMismatch {
+                stored: stored_symbols,
+                built,
+            });
+        }
+
+        let rule_set: RuleSet =
+            bincode::deserialize(rest).map_err(|e| RuleSetError::Decode(e.to_string()))?;
+        rule_set.validate()?;
+        Ok(rule_set)
+    }
+}
+
+#[cfg(all(test, feature = "constructors"))]
+mod test {
+    use super::*;
+
+    /// the frontend can load the bundled file, the program plays the registry table
+    #[test]
+    fn test_default_file_matches_registry() {
+        use crate::registry::RuleSetId;
+
+        let file = include_bytes!("default_pacanea_rule_set.bin");
+        assert_eq!(
+            header_content_hash(file),
+            Some(RuleSetId::DEFAULT.content_hash()),
+            "default_pacanea_rule_set.bin is not {}, run `rules publish`",
+            RuleSetId::DEFAULT
+        );
+        let r = RuleSet::default_internal_deserialize().unwrap();
+        assert_eq!(r.serialize(), RuleSetId::DEFAULT.rule_set().serialize());
+    }
+
+    #[test]
+    fn test_broken_files() {
+        let v = RuleSet::p96().serialize();
+
+        assert_eq!(
+            RuleSet::deserialize(&v[..3]).unwrap_err(),
+            RuleSetError::BadMagic
+        );
+        assert_eq!(header_content_hash(&v[..10]), None);
+        assert_eq!(
+            RuleSet::deserialize(&v[..10]).unwrap_err(),
+            RuleSetError::Truncated
+        );
+        let raw_bincode = bincode::serialize(&RuleSet::p96()).unwrap();
+        assert_eq!(
+            RuleSet::deserialize(&raw_bincode).unwrap_err(),
+            RuleSetError::BadMagic
+        );
+
+        let mut old = v.clone();
+        old[4] = 0;
+        assert_eq!(
+            RuleSet::deserialize(&old).unwrap_err(),
+            RuleSetError::UnsupportedVersion(0)
+        );
+
+        for cut in [HEADER_LEN, HEADER_LEN + 5, v.len() - 1] {
+            assert!(matches!(
+                RuleSet::deserialize(&v[..cut]).unwrap_err(),
+                RuleSetError::HashMismatch { .. }
+            ));
+        }
+
+        // rename a fruit and fix up the hash, as if the file came from another build
+        let mut renamed = v.clone();
+        renamed[HEADER_LEN + 2] ^= 0x20;
+        let hash = content_hash(&renamed[HEADER_LEN..]);
+        renamed[6..HEADER_LEN].copy_from_slice(&hash.to_le_bytes());
+        assert!(matches!(
+            RuleSet::deserialize(&renamed).unwrap_err(),
+            RuleSetError::SymbolMismatch { .. }
+        ));
+    }
+}
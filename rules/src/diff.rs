@@ -0,0 +1,274 @@
+use std::collections::BTreeSet;
+
+use crate::rule_set::{reward_multiple, JackpotRule, RuleSet, ScatterRule};
+use crate::Fruit;
+
+/// Value in the old and in the new rule set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct Shift<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> Shift<T> {
+    fn new(before: T, after: T) -> Self {
+        Shift { before, after }
+    }
+    fn changed(before: T, after: T) -> Option<Self> {
+        (before != after).then_some(Shift { before, after })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ProbChange {
+    /// `None` = the same change on every wheel
+    pub wheel: Option<usize>,
+    pub fruit: Fruit,
+    /// out of u16::MAX, 0 if the fruit was not on the wheel
+    pub prob: Shift<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RewardChange {
+    pub fruit: Fruit,
+    pub count: u8,
+    /// reward in basis points of the bet, `None` when the combo pays nothing
+    pub reward: Shift<Option<u32>>,
+}
+
+/// What changed from one rule set to another, see `RuleSet::diff`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RuleSetDiff {
+    pub wheel_count: Option<Shift<u8>>,
+    pub prob: Vec<ProbChange>,
+    pub rewards: Vec<RewardChange>,
+    pub wilds: Option<Shift<BTreeSet<Fruit>>>,
+    pub scatter: Option<Shift<Option<ScatterRule>>>,
+    pub jackpot: Option<Shift<Option<JackpotRule>>>,
+    pub max_win: Option<Shift<u32>>,
+    /// these come from the par sheets, they are there even if nothing changed
+    pub rtp: Shift<f64>,
+    pub hit_frequency: Shift<f64>,
+    pub max_exposure: Shift<u32>,
+}
+
+impl RuleSet {
+    pub fn diff(&self, after: &RuleSet) -> RuleSetDiff {
+        let before = self;
+        let wheels = before.wheel_count.max(after.wheel_count) as usize;
+        let prob_on = |r: &RuleSet, wheel: usize, fruit: &Fruit| {
+            r.reels()
+                .get(wheel)
+                .and_then(|p| p.get(fruit).copied())
+                .unwrap_or(0)
+        };
+
+        let mut prob = vec![];
+        for fruit in Fruit::all() {
+            let shifts: Vec<Shift<u16>> = (0..wheels)
+                .map(|w| Shift::new(prob_on(before, w, fruit), prob_on(after, w, fruit)))
+                .collect();
+            if shifts.iter().all(|s| *s == shifts[0]) {
+                if shifts[0].before != shifts[0].after {
+                    prob.push(ProbChange {
+                        wheel: None,
+                        fruit: *fruit,
+                        prob: shifts[0],
+                    });
+                }
+                continue;
+            }
+            for (wheel, shift) in shifts.into_iter().enumerate() {
+                if shift.before != shift.after {
+                    prob.push(ProbChange {
+                        wheel: Some(wheel),
+                        fruit: *fruit,
+                        prob: shift,
+                    });
+                }
+            }
+        }
+
+        let combos: BTreeSet<(Fruit, u8)> = before
+            .rewards
+            .keys()
+            .chain(after.rewards.keys())
+            .copied()
+            .collect();
+        let rewards = combos
+            .into_iter()
+            .filter_map(|(fruit, count)| {
+                let reward = Shift::changed(
+                    before.rewards.get(&(fruit, count)).copied(),
+                    after.rewards.get(&(fruit, count)).copied(),
+                )?;
+                Some(RewardChange {
+                    fruit,
+                    count,
+                    reward,
+                })
+            })
+            .collect();
+
+        let (sheet_before, sheet_after) = (before.par_sheet(), after.par_sheet());
+        RuleSetDiff {
+            wheel_count: Shift::changed(before.wheel_count, after.wheel_count),
+            prob,
+            rewards,
+            wilds: Shift::changed(before.wilds.clone(), after.wilds.clone()),
+            scatter: Shift::changed(before.scatter, after.scatter),
+            jackpot: Shift::changed(before.jackpot, after.jackpot),
+            max_win: Shift::changed(before.max_win, after.max_win),
+            rtp: Shift::new(sheet_before.rtp, sheet_after.rtp),
+            hit_frequency: Shift::new(sheet_before.hit_frequency, sheet_after.hit_frequency),
+            max_exposure: Shift::new(sheet_before.max_exposure, sheet_after.max_exposure),
+        }
+    }
+}
+
+fn or_none<T: std::fmt::Debug>(v: &Option<T>) -> String {
+    match v {
+        Some(v) => format!("{v:?}"),
+        None => "-".to_string(),
+    }
+}
+
+impl RuleSetDiff {
+    /// true if both rule sets play the same
+    pub fn is_empty(&self) -> bool {
+        self.wheel_count.is_none()
+            && self.prob.is_empty()
+            && self.rewards.is_empty()
+            && self.wilds.is_none()
+            && self.scatter.is_none()
+            && self.jackpot.is_none()
+            && self.max_win.is_none()
+    }
+
+    /// markdown tables: the summary shift, then every changed probability and reward
+    pub fn to_table(&self) -> String {
+        let mut md = "| metric | before | after | change |\n|---|---|---|---|\n".to_string();
+        let Shift { before, after } = self.rtp;
+        md.push_str(&format!(
+            "| rtp | {before:.6} | {after:.6} | {:+.6} |\n",
+            after - before
+        ));
+        let Shift { before, after } = self.hit_frequency;
+        md.push_str(&format!(
+            "| hit_frequency | {before:.6} | {after:.6} | {:+.6} |\n",
+            after - before
+        ));
+        let Shift { before, after } = self.max_exposure;
+        md.push_str(&format!(
+            "| max_exposure | {} | {} | {:+} |\n",
+            reward_multiple(before),
+            reward_multiple(after),
+            reward_multiple(after) - reward_multiple(before)
+        ));
+        if let Some(Shift { before, after }) = self.wheel_count {
+            md.push_str(&format!(
+                "| wheel_count | {before} | {after} | {:+} |\n",
+                after as i32 - before as i32
+            ));
+        }
+        if let Some(Shift { before, after }) = self.max_win {
+            md.push_str(&format!(
+                "| max_win | {} | {} | {:+} |\n",
+                reward_multiple(before),
+                reward_multiple(after),
+                reward_multiple(after) - reward_multiple(before)
+            ));
+        }
+        if let Some(Shift { before, after }) = &self.wilds {
+            md.push_str(&format!("| wilds | {before:?} | {after:?} | |\n"));
+        }
+        if let Some(Shift { before, after }) = &self.scatter {
+            md.push_str(&format!(
+                "| scatter | {} | {} | |\n",
+                or_none(before),
+                or_none(after)
+            ));
+        }
+        if let Some(Shift { before, after }) = &self.jackpot {
+            md.push_str(&format!(
+                "| jackpot | {} | {} | |\n",
+                or_none(before),
+                or_none(after)
+            ));
+        }
+
+        if !self.prob.is_empty() {
+            md.push_str("\n| wheel | fruit | before | after | change |\n|---|---|---|---|---|\n");
+            for c in self.prob.iter() {
+                let wheel = c.wheel.map(|w| w.to_string()).unwrap_or("all".to_string());
+                let Shift { before, after } = c.prob;
+                md.push_str(&format!(
+                    "| {wheel} | {} | {before} | {after} | {:+} |\n",
+                    c.fruit,
+                    after as i32 - before as i32
+                ));
+            }
+        }
+        if !self.rewards.is_empty() {
+            md.push_str("\n| fruit | count | before | after |\n|---|---|---|---|\n");
+            for c in self.rewards.iter() {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    c.fruit,
+                    c.count,
+                    or_none(&c.reward.before.map(reward_multiple)),
+                    or_none(&c.reward.after.map(reward_multiple))
+                ));
+            }
+        }
+        md
+    }
+
+    #[cfg(feature = "formats")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+#[cfg(all(test, feature = "constructors"))]
+mod test {
+    use super::*;
+    use crate::rule_set::REWARD_ONE;
+
+    #[test]
+    fn test_diff() {
+        let a = RuleSet::p96();
+        assert!(a.diff(&a).is_empty());
+
+        let mut b = RuleSet::p96();
+        *b.prob.get_mut(&Fruit::cherry).unwrap() -= 100;
+        *b.prob.get_mut(&Fruit::seven).unwrap() += 100;
+        let mut last = b.prob();
+        *last.get_mut(&Fruit::bell).unwrap() -= 5;
+        *last.get_mut(&Fruit::kiwi).unwrap() += 5;
+        b.set_reel_prob(vec![b.prob(), b.prob(), last]);
+        b.rewards.insert((Fruit::seven, 3), 5_000_000);
+        b.rewards.remove(&(Fruit::bananas, 2));
+        b.set_max_win(500 * REWARD_ONE);
+
+        let d = a.diff(&b);
+        assert!(!d.is_empty());
+        let cherry = d.prob.iter().find(|c| c.fruit == Fruit::cherry).unwrap();
+        assert_eq!(cherry.wheel, None);
+        assert_eq!(cherry.prob.after + 100, cherry.prob.before);
+        let bell: Vec<_> = d.prob.iter().filter(|c| c.fruit == Fruit::bell).collect();
+        assert_eq!(bell.len(), 1);
+        assert_eq!(bell[0].wheel, Some(2));
+
+        let bananas = d
+            .rewards
+            .iter()
+            .find(|c| c.fruit == Fruit::bananas)
+            .unwrap();
+        assert_eq!(bananas.reward.after, None);
+        assert_eq!(d.max_exposure.after, 5_000_000);
+        assert_eq!(d.max_win.unwrap().after, 500 * REWARD_ONE);
+        assert_ne!(d.rtp.after, d.rtp.before);
+        assert!(d.to_table().contains("| all | cherry |"));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::Fruit;
+
+/// Why a rule set could not be loaded, or is not safe to play.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleSetError {
+    /// the file ends before the header or symbol list does
+    Truncated,
+    /// the file does not start with `container::MAGIC`, e.g. a raw bincode file
+    BadMagic,
+    UnsupportedVersion(u16),
+    HashMismatch {
+        stored: u64,
+        computed: u64,
+    },
+    /// the file was built against other fruits than this build has
+    SymbolMismatch {
+        stored: Vec<String>,
+        built: Vec<String>,
+    },
+    Decode(String),
+    /// bad JSON or TOML
+    Parse(String),
+    UnknownFruit(String),
+
+    WheelCount(u8),
+    /// `max_win` must let some win through
+    MaxWin(u32),
+    /// `reel_prob` must be empty or have one table per wheel
+    ReelCount {
+        wheel_count: u8,
+        reels: usize,
+    },
+    /// every probability table must sum to u16::MAX
+    ProbSum {
+        reel: Option<usize>,
+        sum: u32,
+    },
+    /// the base probability table must list every fruit
+    MissingFruit(Fruit),
+    /// every wheel needs at least 3 fruits that can show up
+    TooFewFruits {
+        reel: Option<usize>,
+    },
+    RewardCount {
+        fruit: Fruit,
+        count: u8,
+    },
+    /// a single reward over `max_win`
+    RewardTooBig {
+        fruit: Fruit,
+        count: u8,
+        reward: u32,
+    },
+    Wilds,
+    Scatter,
+    /// every spin wins 1 or more free spins on average, they never end
+    EndlessFreeSpins,
+    Jackpot,
+}
+
+impl std::fmt::Display for RuleSetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use RuleSetError::*;
+        let reel_name = |reel: &Option<usize>| match reel {
+            Some(i) => format!("wheel {i}"),
+            None => "base table".to_string(),
+        };
+        match self {
+            Truncated => write!(f, "rule set file is truncated"),
+            BadMagic => write!(f, "not a rule set file"),
+            UnsupportedVersion(v) => write!(f, "unsupported rule set format version {v}"),
+            HashMismatch { stored, computed } => {
+                write!(
+                    f,
+                    "content hash mismatch: stored {stored:016x}, computed {computed:016x}"
+                )
+            }
+            SymbolMismatch { stored, built } => write!(
+                f,
+                "rule set built for fruits [{}], this build has [{}]",
+                stored.join(", "),
+                built.join(", ")
+            ),
+            Decode(e) => write!(f, "rule set decode error: {e}"),
+            Parse(e) => write!(f, "rule set parse error: {e}"),
+            UnknownFruit(name) => write!(f, "unknown fruit {name:?}"),
+            WheelCount(n) => write!(f, "bad wheel count {n}"),
+            MaxWin(max_win) => write!(f, "bad max_win {max_win}"),
+            ReelCount { wheel_count, reels } => {
+                write!(f, "{reels} probability tables for {wheel_count} wheels")
+            }
+            ProbSum { reel, sum } => {
+                write!(f, "{} sums to {sum}, not {}", reel_name(reel), u16::MAX)
+            }
+            MissingFruit(fruit) => write!(f, "base table has no {fruit}"),
+            TooFewFruits { reel } => write!(f, "{} has less than 3 fruits", reel_name(reel)),
+            RewardCount { fruit, count } => write!(f, "reward for {count} x {fruit} can never hit"),
+            RewardTooBig {
+                fruit,
+                count,
+                reward,
+            } => write!(f, "reward {reward} for {count} x {fruit} is over max_win"),
+            Wilds => write!(f, "every fruit is wild"),
+            Scatter => write!(f, "bad scatter rule"),
+            EndlessFreeSpins => write!(f, "free spins win more free spins than they use up"),
+            Jackpot => write!(f, "bad jackpot rule"),
+        }
+    }
+}
+
+impl std::error::Error for RuleSetError {}
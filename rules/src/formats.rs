@@ -0,0 +1,286 @@
+//! Hand editable JSON and TOML rule sets. Fruits are written by name and rewards
+//! are grouped by fruit, then by how many times it hits. Rewards are in basis
+//! points of the bet, 15000 pays 1.5x:
+//!
+//! ```toml
+//! wheel_count = 3
+//! wilds = []
+//! max_win = 10000000
+//!
+//! [prob]
+//! cherry = 9000
+//!
+//! [rewards.cherry]
+//! 1 = 10000
+//! 3 = 250000
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use crate::error::RuleSetError;
+use crate::rule_set::{JackpotRule, RuleSet, ScatterRule};
+use crate::Fruit;
+
+// a misspelled optional key must not silently drop that rule
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSetDoc {
+    wheel_count: u8,
+    #[serde(default)]
+    wilds: Vec<String>,
+    /// basis points of the bet, like the rewards
+    max_win: u32,
+    prob: BTreeMap<String, u16>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    reel_prob: Vec<BTreeMap<String, u16>>,
+    rewards: BTreeMap<String, BTreeMap<String, u32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scatter: Option<ScatterDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    jackpot: Option<JackpotDoc>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScatterDoc {
+    fruit: String,
+    min_count: u8,
+    free_spins: u8,
+    win_multiplier: u8,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JackpotDoc {
+    fruit: String,
+    count: u8,
+    contribution_bps: u16,
+}
+
+fn fruit(name: &str) -> Result<Fruit, RuleSetError> {
+    Fruit::from_str(name).map_err(|_| RuleSetError::UnknownFruit(name.to_string()))
+}
+
+fn named_table(prob: &BTreeMap<Fruit, u16>) -> BTreeMap<String, u16> {
+    prob.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+}
+
+fn fruit_table(prob: &BTreeMap<String, u16>) -> Result<BTreeMap<Fruit, u16>, RuleSetError> {
+    prob.iter().map(|(k, v)| Ok((fruit(k)?, *v))).collect()
+}
+
+impl From<&RuleSet> for RuleSetDoc {
+    fn from(r: &RuleSet) -> Self {
+        let mut rewards = BTreeMap::<String, BTreeMap<String, u32>>::new();
+        for ((fruit, count), reward) in r.rewards.iter() {
+            rewards
+                .entry(fruit.to_string())
+                .or_default()
+                .insert(count.to_string(), *reward);
+        }
+        RuleSetDoc {
+            wheel_count: r.wheel_count,
+            wilds: r.wilds.iter().map(|f| f.to_string()).collect(),
+            max_win: r.max_win,
+            prob: named_table(&r.prob),
+            reel_prob: r.reel_prob.iter().map(named_table).collect(),
+            rewards,
+            scatter: r.scatter.map(|s| ScatterDoc {
+                fruit: s.fruit.to_string(),
+                min_count: s.min_count,
+                free_spins: s.free_spins,
+                win_multiplier: s.win_multiplier,
+            }),
+            jackpot: r.jackpot.map(|j| JackpotDoc {
+                fruit: j.fruit.to_string(),
+                count: j.count,
+                contribution_bps: j.contribution_bps,
+            }),
+        }
+    }
+}
+
+impl TryFrom<RuleSetDoc> for RuleSet {
+    type Error = RuleSetError;
+
+    fn try_from(doc: RuleSetDoc) -> Result<Self, RuleSetError> {
+        let mut rewards = BTreeMap::new();
+        for (name, counts) in doc.rewards.iter() {
+            let fruit = fruit(name)?;
+            for (count, reward) in counts.iter() {
+                let count = count.parse().map_err(|_| {
+                    RuleSetError::Parse(format!("bad hit count {count:?} for {name}"))
+                })?;
+                rewards.insert((fruit, count), *reward);
+            }
+        }
+        let scatter = match doc.scatter {
+            Some(s) => Some(ScatterRule {
+                fruit: fruit(&s.fruit)?,
+                min_count: s.min_count,
+                free_spins: s.free_spins,
+                win_multiplier: s.win_multiplier,
+            }),
+            None => None,
+        };
+        let jackpot = match doc.jackpot {
+            Some(j) => Some(JackpotRule {
+                fruit: fruit(&j.fruit)?,
+                count: j.count,
+                contribution_bps: j.contribution_bps,
+            }),
+            None => None,
+        };
+        let rule_set = RuleSet {
+            prob: fruit_table(&doc.prob)?,
+            rewards,
+            wheel_count: doc.wheel_count,
+            reel_prob: doc
+                .reel_prob
+                .iter()
+                .map(fruit_table)
+                .collect::<Result<_, _>>()?,
+            wilds: doc
+                .wilds
+                .iter()
+                .map(|w| fruit(w))
+                .collect::<Result<BTreeSet<_>, _>>()?,
+            scatter,
+            jackpot,
+            max_win: doc.max_win,
+        };
+        rule_set.validate()?;
+        Ok(rule_set)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `container` file, see `RuleSet::serialize`
+    Bin,
+    Json,
+    Toml,
+}
+
+impl Format {
+    /// by file extension, `None` if it is not one of ours
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = std::path::Path::new(path).extension()?.to_str()?;
+        match ext.to_lowercase().as_str() {
+            "bin" => Some(Format::Bin),
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+}
+
+impl RuleSet {
+    pub fn encode(&self, format: Format) -> Vec<u8> {
+        match format {
+            Format::Bin => self.serialize(),
+            Format::Json => self.to_json().into_bytes(),
+            Format::Toml => self.to_toml().into_bytes(),
+        }
+    }
+
+    pub fn decode(format: Format, v: &[u8]) -> Result<Self, RuleSetError> {
+        let text = || std::str::from_utf8(v).map_err(|e| RuleSetError::Parse(e.to_string()));
+        match format {
+            Format::Bin => Self::deserialize(v),
+            Format::Json => Self::from_json(text()?),
+            Format::Toml => Self::from_toml(text()?),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&RuleSetDoc::from(self)).unwrap()
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, RuleSetError> {
+        let doc: RuleSetDoc =
+            serde_json::from_str(s).map_err(|e| RuleSetError::Parse(e.to_string()))?;
+        doc.try_into()
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(&RuleSetDoc::from(self)).unwrap()
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, RuleSetError> {
+        let doc: RuleSetDoc = toml::from_str(s).map_err(|e| RuleSetError::Parse(e.to_string()))?;
+        doc.try_into()
+    }
+}
+
+#[cfg(all(test, feature = "generate"))]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::rule_set::RewardShape;
+
+    fn fancy_rule_set() -> RuleSet {
+        let mut r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(14),
+            0.9,
+            3,
+            &[Fruit::dollars],
+            &RewardShape::default(),
+        );
+        r.rewards.insert((Fruit::cherry, 1), 10000);
+        let mut last = r.prob();
+        let sevens = last.remove(&Fruit::seven).unwrap();
+        *last.get_mut(&Fruit::bananas).unwrap() += sevens;
+        r.set_reel_prob(vec![r.prob(), r.prob(), last]);
+        r.set_scatter(Some(ScatterRule {
+            fruit: Fruit::bell,
+            min_count: 3,
+            free_spins: 5,
+            win_multiplier: 2,
+        }));
+        r.set_jackpot(Some(JackpotRule {
+            fruit: Fruit::seven,
+            count: 3,
+            contribution_bps: 100,
+        }));
+        r
+    }
+
+    #[test]
+    fn test_json_toml_roundtrip() {
+        let r = fancy_rule_set();
+        let bin = r.serialize();
+        assert_eq!(RuleSet::from_json(&r.to_json()).unwrap().serialize(), bin);
+        assert_eq!(RuleSet::from_toml(&r.to_toml()).unwrap().serialize(), bin);
+    }
+
+    #[test]
+    fn test_hand_edit() {
+        let r = fancy_rule_set();
+        let text = r
+            .to_toml()
+            .replace("[rewards.cherry]\n1 = 10000", "[rewards.cherry]\n1 = 15000");
+        let edited = RuleSet::from_toml(&text).unwrap();
+        assert_eq!(edited.rewards()[&(Fruit::cherry, 1)], 15000);
+
+        let text = r.to_json().replace("\"cherry\"", "\"mango\"");
+        assert_eq!(
+            RuleSet::from_json(&text).unwrap_err(),
+            RuleSetError::UnknownFruit("mango".to_string())
+        );
+
+        let text = r.to_json().replace("\"jackpot\"", "\"jackpott\"");
+        assert!(matches!(
+            RuleSet::from_json(&text),
+            Err(RuleSetError::Parse(_))
+        ));
+        let text = r.to_toml().replace("win_multiplier", "win_multiplyer");
+        assert!(matches!(
+            RuleSet::from_toml(&text),
+            Err(RuleSetError::Parse(_))
+        ));
+    }
+}
@@ -1 +1,3 @@
-pub mod p96;
+pub mod p90;
+pub mod p94;
+pub mod p96;
@@ -0,0 +1,115 @@
+use crate::rule_set::RuleSet;
+use crate::Fruit;
+use std::collections::{BTreeMap, BTreeSet};
+impl RuleSet {
+    pub fn p90() -> Self {
+        Self {
+            prob: BTreeMap::<Fruit, u16>::from([
+                (Fruit::bananas, 5418),
+                (Fruit::bell, 4474),
+                (Fruit::watermelon, 4469),
+                (Fruit::one_bar, 4420),
+                (Fruit::diamond2, 4095),
+                (Fruit::grapes, 3984),
+                (Fruit::clover, 3900),
+                (Fruit::crown, 3886),
+                (Fruit::lemon, 3556),
+                (Fruit::diamond, 3379),
+                (Fruit::cherry, 3161),
+                (Fruit::bonus, 2677),
+                (Fruit::dollars, 2628),
+                (Fruit::orange, 2286),
+                (Fruit::heart, 2213),
+                (Fruit::kiwi, 2204),
+                (Fruit::bar_bar_bar, 2202),
+                (Fruit::diamond3, 2194),
+                (Fruit::strawberry, 1980),
+                (Fruit::big_win, 1280),
+                (Fruit::seven, 1129),
+            ]),
+            rewards: BTreeMap::<(Fruit, u8), u32>::from([
+                ((Fruit::bananas, 2), 32078),
+                ((Fruit::bananas, 3), 219479),
+                ((Fruit::bell, 2), 39239),
+                ((Fruit::bell, 3), 300731),
+                ((Fruit::watermelon, 2), 39287),
+                ((Fruit::watermelon, 3), 301289),
+                ((Fruit::one_bar, 2), 39745),
+                ((Fruit::one_bar, 3), 306829),
+                ((Fruit::diamond2, 2), 43099),
+                ((Fruit::diamond2, 3), 347906),
+                ((Fruit::grapes, 2), 44372),
+                ((Fruit::grapes, 3), 364024),
+                ((Fruit::clover, 2), 45394),
+                ((Fruit::clover, 3), 377052),
+                ((Fruit::crown, 2), 45557),
+                ((Fruit::crown, 3), 379304),
+                ((Fruit::lemon, 2), 50086),
+                ((Fruit::lemon, 3), 438932),
+                ((Fruit::diamond, 2), 52873),
+                ((Fruit::diamond, 3), 477334),
+                ((Fruit::cherry, 2), 56777),
+                ((Fruit::cherry, 3), 532775),
+                ((Fruit::bonus, 2), 67856),
+                ((Fruit::bonus, 3), 700373),
+                ((Fruit::dollars, 2), 69213),
+                ((Fruit::dollars, 3), 722021),
+                ((Fruit::orange, 2), 80408),
+                ((Fruit::orange, 3), 903389),
+                ((Fruit::heart, 2), 83276),
+                ((Fruit::heart, 3), 903528),
+                ((Fruit::kiwi, 2), 83665),
+                ((Fruit::kiwi, 3), 903550),
+                ((Fruit::bar_bar_bar, 2), 83715),
+                ((Fruit::bar_bar_bar, 3), 903296),
+                ((Fruit::diamond3, 2), 84059),
+                ((Fruit::diamond3, 3), 903543),
+                ((Fruit::strawberry, 2), 93903),
+                ((Fruit::strawberry, 3), 903289),
+                ((Fruit::big_win, 2), 150628),
+                ((Fruit::big_win, 3), 903546),
+                ((Fruit::seven, 2), 172668),
+                ((Fruit::seven, 3), 903253),
+            ]),
+            wheel_count: 3,
+            reel_prob: vec![],
+            wilds: BTreeSet::<Fruit>::from([]),
+            scatter: None,
+            jackpot: None,
+            max_win: 10000000,
+        }
+    }
+}
+/// `RuleSet::content_hash` of `p90()`
+pub const P90_HASH: u64 = 0x23f9b8bb632ef75b;
+pub const P90: crate::compiled::CompiledRuleSet<'static> = crate::compiled::CompiledRuleSet {
+    wheel_count: 3,
+    cdf: &[
+        [
+            5418, 9892, 14361, 18781, 22876, 26860, 30760, 34646, 38202, 41581, 44742, 47419,
+            50047, 52333, 54546, 56750, 58952, 61146, 63126, 64406, 65535,
+        ],
+        [
+            5418, 9892, 14361, 18781, 22876, 26860, 30760, 34646, 38202, 41581, 44742, 47419,
+            50047, 52333, 54546, 56750, 58952, 61146, 63126, 64406, 65535,
+        ],
+        [
+            5418, 9892, 14361, 18781, 22876, 26860, 30760, 34646, 38202, 41581, 44742, 47419,
+            50047, 52333, 54546, 56750, 58952, 61146, 63126, 64406, 65535,
+        ],
+    ],
+    rewards: &[
+        0, 0, 32078, 219479, 0, 0, 39239, 300731, 0, 0, 39287, 301289, 0, 0, 39745, 306829, 0, 0,
+        43099, 347906, 0, 0, 44372, 364024, 0, 0, 45394, 377052, 0, 0, 45557, 379304, 0, 0, 50086,
+        438932, 0, 0, 52873, 477334, 0, 0, 56777, 532775, 0, 0, 67856, 700373, 0, 0, 69213, 722021,
+        0, 0, 80408, 903389, 0, 0, 83276, 903528, 0, 0, 83665, 903550, 0, 0, 83715, 903296, 0, 0,
+        84059, 903543, 0, 0, 93903, 903289, 0, 0, 150628, 903546, 0, 0, 172668, 903253,
+    ],
+    wilds: [
+        false, false, false, false, false, false, false, false, false, false, false, false, false,
+        false, false, false, false, false, false, false, false,
+    ],
+    scatter: None,
+    jackpot: None,
+    max_win: 10000000,
+};
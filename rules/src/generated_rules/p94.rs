@@ -0,0 +1,116 @@
+use crate::rule_set::RuleSet;
+use crate::Fruit;
+use std::collections::{BTreeMap, BTreeSet};
+impl RuleSet {
+    pub fn p94() -> Self {
+        Self {
+            prob: BTreeMap::<Fruit, u16>::from([
+                (Fruit::bananas, 5772),
+                (Fruit::bell, 4502),
+                (Fruit::watermelon, 4395),
+                (Fruit::one_bar, 4349),
+                (Fruit::diamond2, 3948),
+                (Fruit::grapes, 3880),
+                (Fruit::clover, 3853),
+                (Fruit::crown, 3666),
+                (Fruit::lemon, 3665),
+                (Fruit::diamond, 3522),
+                (Fruit::cherry, 3383),
+                (Fruit::bonus, 3066),
+                (Fruit::dollars, 2537),
+                (Fruit::orange, 2512),
+                (Fruit::heart, 2127),
+                (Fruit::kiwi, 1993),
+                (Fruit::bar_bar_bar, 1816),
+                (Fruit::diamond3, 1814),
+                (Fruit::strawberry, 1693),
+                (Fruit::big_win, 1526),
+                (Fruit::seven, 1516),
+            ]),
+            rewards: BTreeMap::<(Fruit, u8), u32>::from([
+                ((Fruit::bananas, 2), 28498),
+                ((Fruit::bananas, 3), 230662),
+                ((Fruit::bell, 2), 38067),
+                ((Fruit::bell, 3), 363138),
+                ((Fruit::watermelon, 2), 39161),
+                ((Fruit::watermelon, 3), 379432),
+                ((Fruit::one_bar, 2), 39648),
+                ((Fruit::one_bar, 3), 386768),
+                ((Fruit::diamond2, 2), 44422),
+                ((Fruit::diamond2, 3), 461543),
+                ((Fruit::grapes, 2), 45338),
+                ((Fruit::grapes, 3), 476421),
+                ((Fruit::clover, 2), 45719),
+                ((Fruit::clover, 3), 482556),
+                ((Fruit::crown, 2), 48494),
+                ((Fruit::crown, 3), 528415),
+                ((Fruit::lemon, 2), 48502),
+                ((Fruit::lemon, 3), 528663),
+                ((Fruit::diamond, 2), 50829),
+                ((Fruit::diamond, 3), 568406),
+                ((Fruit::cherry, 2), 53322),
+                ((Fruit::cherry, 3), 611919),
+                ((Fruit::bonus, 2), 59914),
+                ((Fruit::bonus, 3), 732258),
+                ((Fruit::dollars, 2), 75058),
+                ((Fruit::dollars, 3), 1034795),
+                ((Fruit::orange, 2), 75957),
+                ((Fruit::orange, 3), 1053740),
+                ((Fruit::heart, 2), 92653),
+                ((Fruit::heart, 3), 1211830),
+                ((Fruit::kiwi, 2), 100163),
+                ((Fruit::kiwi, 3), 1211602),
+                ((Fruit::bar_bar_bar, 2), 112000),
+                ((Fruit::bar_bar_bar, 3), 1211782),
+                ((Fruit::diamond3, 2), 112129),
+                ((Fruit::diamond3, 3), 1211775),
+                ((Fruit::strawberry, 2), 121838),
+                ((Fruit::strawberry, 3), 1211967),
+                ((Fruit::big_win, 2), 138025),
+                ((Fruit::big_win, 3), 1211799),
+                ((Fruit::seven, 2), 139128),
+                ((Fruit::seven, 3), 1211637),
+            ]),
+            wheel_count: 3,
+            reel_prob: vec![],
+            wilds: BTreeSet::<Fruit>::from([]),
+            scatter: None,
+            jackpot: None,
+            max_win: 10000000,
+        }
+    }
+}
+/// `RuleSet::content_hash` of `p94()`
+pub const P94_HASH: u64 = 0x7b5fa6e2e6d7c537;
+pub const P94: crate::compiled::CompiledRuleSet<'static> = crate::compiled::CompiledRuleSet {
+    wheel_count: 3,
+    cdf: &[
+        [
+            5772, 10274, 14669, 19018, 22966, 26846, 30699, 34365, 38030, 41552, 44935, 48001,
+            50538, 53050, 55177, 57170, 58986, 60800, 62493, 64019, 65535,
+        ],
+        [
+            5772, 10274, 14669, 19018, 22966, 26846, 30699, 34365, 38030, 41552, 44935, 48001,
+            50538, 53050, 55177, 57170, 58986, 60800, 62493, 64019, 65535,
+        ],
+        [
+            5772, 10274, 14669, 19018, 22966, 26846, 30699, 34365, 38030, 41552, 44935, 48001,
+            50538, 53050, 55177, 57170, 58986, 60800, 62493, 64019, 65535,
+        ],
+    ],
+    rewards: &[
+        0, 0, 28498, 230662, 0, 0, 38067, 363138, 0, 0, 39161, 379432, 0, 0, 39648, 386768, 0, 0,
+        44422, 461543, 0, 0, 45338, 476421, 0, 0, 45719, 482556, 0, 0, 48494, 528415, 0, 0, 48502,
+        528663, 0, 0, 50829, 568406, 0, 0, 53322, 611919, 0, 0, 59914, 732258, 0, 0, 75058,
+        1034795, 0, 0, 75957, 1053740, 0, 0, 92653, 1211830, 0, 0, 100163, 1211602, 0, 0, 112000,
+        1211782, 0, 0, 112129, 1211775, 0, 0, 121838, 1211967, 0, 0, 138025, 1211799, 0, 0, 139128,
+        1211637,
+    ],
+    wilds: [
+        false, false, false, false, false, false, false, false, false, false, false, false, false,
+        false, false, false, false, false, false, false, false,
+    ],
+    scatter: None,
+    jackpot: None,
+    max_win: 10000000,
+};
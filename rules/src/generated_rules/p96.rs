@@ -1,76 +1,133 @@
-
-        use crate::Fruit;
-        use crate::rule_set::RuleSet;
-        use std::collections::BTreeMap;
-        impl RuleSet {
-            pub fn p96() -> Self {
-                Self {
-                    prob: BTreeMap::<Fruit, u16>::from([(Fruit::bananas, 4894),
-(Fruit::bell, 3990),
-(Fruit::watermelon, 3915),
-(Fruit::one_bar, 3887),
-(Fruit::diamond2, 3853),
-(Fruit::grapes, 3849),
-(Fruit::clover, 3739),
-(Fruit::crown, 3349),
-(Fruit::lemon, 3255),
-(Fruit::diamond, 3200),
-(Fruit::cherry, 3084),
-(Fruit::bonus, 2951),
-(Fruit::dollars, 2924),
-(Fruit::orange, 2589),
-(Fruit::heart, 2539),
-(Fruit::kiwi, 2409),
-(Fruit::bar_bar_bar, 2353),
-(Fruit::diamond3, 2340),
-(Fruit::strawberry, 2225),
-(Fruit::big_win, 2150),
-(Fruit::seven, 2040)]),
-                    rewards: BTreeMap::<(Fruit, u8), u16>::from([((Fruit::bananas, 2), 2),
-((Fruit::bananas, 3), 37),
-((Fruit::bell, 2), 3),
-((Fruit::bell, 3), 63),
-((Fruit::watermelon, 2), 3),
-((Fruit::watermelon, 3), 66),
-((Fruit::one_bar, 2), 3),
-((Fruit::one_bar, 3), 67),
-((Fruit::diamond2, 2), 3),
-((Fruit::diamond2, 3), 70),
-((Fruit::grapes, 2), 3),
-((Fruit::grapes, 3), 70),
-((Fruit::clover, 2), 3),
-((Fruit::clover, 3), 74),
-((Fruit::crown, 2), 3),
-((Fruit::crown, 3), 100),
-((Fruit::lemon, 2), 3),
-((Fruit::lemon, 3), 106),
-((Fruit::diamond, 2), 3),
-((Fruit::diamond, 3), 110),
-((Fruit::cherry, 1), 1),
-((Fruit::cherry, 2), 3),
-((Fruit::cherry, 3), 122),
-((Fruit::bonus, 2), 3),
-((Fruit::bonus, 3), 137),
-((Fruit::dollars, 2), 4),
-((Fruit::dollars, 3), 141),
-((Fruit::orange, 2), 5),
-((Fruit::orange, 3), 190),
-((Fruit::heart, 2), 5),
-((Fruit::heart, 3), 199),
-((Fruit::kiwi, 2), 5),
-((Fruit::kiwi, 3), 227),
-((Fruit::bar_bar_bar, 2), 6),
-((Fruit::bar_bar_bar, 3), 240),
-((Fruit::diamond3, 2), 6),
-((Fruit::diamond3, 3), 243),
-((Fruit::strawberry, 2), 6),
-((Fruit::strawberry, 3), 275),
-((Fruit::big_win, 2), 7),
-((Fruit::big_win, 3), 300),
-((Fruit::seven, 2), 7),
-((Fruit::seven, 3), 339)]),
-                    wheel_count: 3,
-                }
-            }
+use crate::rule_set::RuleSet;
+use crate::Fruit;
+use std::collections::{BTreeMap, BTreeSet};
+impl RuleSet {
+    pub fn p96() -> Self {
+        Self {
+            prob: BTreeMap::<Fruit, u16>::from([
+                (Fruit::bananas, 5885),
+                (Fruit::bell, 4627),
+                (Fruit::watermelon, 4508),
+                (Fruit::one_bar, 4494),
+                (Fruit::diamond2, 3620),
+                (Fruit::grapes, 3549),
+                (Fruit::clover, 3453),
+                (Fruit::crown, 3320),
+                (Fruit::lemon, 3128),
+                (Fruit::diamond, 3070),
+                (Fruit::cherry, 2987),
+                (Fruit::bonus, 2949),
+                (Fruit::dollars, 2551),
+                (Fruit::orange, 2396),
+                (Fruit::heart, 2372),
+                (Fruit::kiwi, 2327),
+                (Fruit::bar_bar_bar, 2256),
+                (Fruit::diamond3, 2251),
+                (Fruit::strawberry, 2085),
+                (Fruit::big_win, 1908),
+                (Fruit::seven, 1799),
+            ]),
+            rewards: BTreeMap::<(Fruit, u8), u32>::from([
+                ((Fruit::bananas, 2), 31232),
+                ((Fruit::bananas, 3), 179016),
+                ((Fruit::bell, 2), 39518),
+                ((Fruit::bell, 3), 258921),
+                ((Fruit::watermelon, 2), 40550),
+                ((Fruit::watermelon, 3), 269491),
+                ((Fruit::one_bar, 2), 40677),
+                ((Fruit::one_bar, 3), 270729),
+                ((Fruit::diamond2, 2), 50362),
+                ((Fruit::diamond2, 3), 377216),
+                ((Fruit::grapes, 2), 51373),
+                ((Fruit::grapes, 3), 388859),
+                ((Fruit::clover, 2), 52789),
+                ((Fruit::clover, 3), 405574),
+                ((Fruit::crown, 2), 54901),
+                ((Fruit::crown, 3), 430760),
+                ((Fruit::lemon, 2), 58248),
+                ((Fruit::lemon, 3), 472029),
+                ((Fruit::diamond, 2), 59341),
+                ((Fruit::diamond, 3), 485713),
+                ((Fruit::cherry, 2), 60985),
+                ((Fruit::cherry, 3), 506582),
+                ((Fruit::bonus, 2), 61777),
+                ((Fruit::bonus, 3), 516671),
+                ((Fruit::dollars, 2), 71411),
+                ((Fruit::dollars, 3), 645271),
+                ((Fruit::orange, 2), 76050),
+                ((Fruit::orange, 3), 710480),
+                ((Fruit::heart, 2), 76821),
+                ((Fruit::heart, 3), 721571),
+                ((Fruit::kiwi, 2), 78304),
+                ((Fruit::kiwi, 3), 735154),
+                ((Fruit::bar_bar_bar, 2), 80793),
+                ((Fruit::bar_bar_bar, 3), 735201),
+                ((Fruit::diamond3, 2), 80950),
+                ((Fruit::diamond3, 3), 735178),
+                ((Fruit::strawberry, 2), 87449),
+                ((Fruit::strawberry, 3), 735139),
+                ((Fruit::big_win, 2), 95595),
+                ((Fruit::big_win, 3), 735272),
+                ((Fruit::seven, 2), 101463),
+                ((Fruit::seven, 3), 735177),
+            ]),
+            wheel_count: 3,
+            reel_prob: vec![],
+            wilds: BTreeSet::<Fruit>::from([]),
+            scatter: Some(crate::rule_set::ScatterRule {
+                fruit: Fruit::bonus,
+                min_count: 2,
+                free_spins: 5,
+                win_multiplier: 2,
+            }),
+            jackpot: Some(crate::rule_set::JackpotRule {
+                fruit: Fruit::seven,
+                count: 3,
+                contribution_bps: 50,
+            }),
+            max_win: 10000000,
         }
-        
\ No newline at end of file
+    }
+}
+/// `RuleSet::content_hash` of `p96()`
+pub const P96_HASH: u64 = 0x84c8c990a11a8442;
+pub const P96: crate::compiled::CompiledRuleSet<'static> = crate::compiled::CompiledRuleSet {
+    wheel_count: 3,
+    cdf: &[
+        [
+            5885, 10512, 15020, 19514, 23134, 26683, 30136, 33456, 36584, 39654, 42641, 45590,
+            48141, 50537, 52909, 55236, 57492, 59743, 61828, 63736, 65535,
+        ],
+        [
+            5885, 10512, 15020, 19514, 23134, 26683, 30136, 33456, 36584, 39654, 42641, 45590,
+            48141, 50537, 52909, 55236, 57492, 59743, 61828, 63736, 65535,
+        ],
+        [
+            5885, 10512, 15020, 19514, 23134, 26683, 30136, 33456, 36584, 39654, 42641, 45590,
+            48141, 50537, 52909, 55236, 57492, 59743, 61828, 63736, 65535,
+        ],
+    ],
+    rewards: &[
+        0, 0, 31232, 179016, 0, 0, 39518, 258921, 0, 0, 40550, 269491, 0, 0, 40677, 270729, 0, 0,
+        50362, 377216, 0, 0, 51373, 388859, 0, 0, 52789, 405574, 0, 0, 54901, 430760, 0, 0, 58248,
+        472029, 0, 0, 59341, 485713, 0, 0, 60985, 506582, 0, 0, 61777, 516671, 0, 0, 71411, 645271,
+        0, 0, 76050, 710480, 0, 0, 76821, 721571, 0, 0, 78304, 735154, 0, 0, 80793, 735201, 0, 0,
+        80950, 735178, 0, 0, 87449, 735139, 0, 0, 95595, 735272, 0, 0, 101463, 735177,
+    ],
+    wilds: [
+        false, false, false, false, false, false, false, false, false, false, false, false, false,
+        false, false, false, false, false, false, false, false,
+    ],
+    scatter: Some(crate::rule_set::ScatterRule {
+        fruit: Fruit::bonus,
+        min_count: 2,
+        free_spins: 5,
+        win_multiplier: 2,
+    }),
+    jackpot: Some(crate::rule_set::JackpotRule {
+        fruit: Fruit::seven,
+        count: 3,
+        contribution_bps: 50,
+    }),
+    max_win: 10000000,
+};
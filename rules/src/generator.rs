@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::par_sheet::ParSheet;
+use crate::rule_set::{reward_multiple, JackpotRule, RewardShape, RuleSet, ScatterRule};
+use crate::Fruit;
+
+/// What a generated rule set has to meet. Checked against the exact par sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorSpec {
+    /// same seed and spec = same rule set
+    pub seed: u64,
+    pub wheel_count: u8,
+    pub wilds: Vec<Fruit>,
+    pub target_rtp: f64,
+    /// accepted distance from `target_rtp`
+    pub rtp_tolerance: f64,
+    pub min_hit_frequency: f64,
+    /// biggest single win, as multiple of the bet
+    pub max_win: u16,
+    /// accepted `volatility_index` range, inclusive
+    pub volatility: (f64, f64),
+    /// free spins and jackpot of every candidate, `target_rtp` includes what they pay
+    pub scatter: Option<ScatterRule>,
+    pub jackpot: Option<JackpotRule>,
+    pub max_attempts: u32,
+}
+
+impl Default for GeneratorSpec {
+    fn default() -> Self {
+        GeneratorSpec {
+            seed: 0,
+            wheel_count: 3,
+            wilds: vec![],
+            target_rtp: 0.90,
+            rtp_tolerance: 0.005,
+            min_hit_frequency: 0.15,
+            max_win: 1000,
+            volatility: (0.0, f64::INFINITY),
+            scatter: None,
+            jackpot: None,
+            max_attempts: 200,
+        }
+    }
+}
+
+/// Ways a candidate can miss the spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SpecMiss {
+    /// the candidate does not validate, e.g. its scatter gives endless free spins
+    Invalid,
+    Rtp,
+    HitFrequency,
+    MaxWin,
+    Volatility,
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneratorReport {
+    pub spec: GeneratorSpec,
+    /// candidates tried, the last one is the accepted one on success
+    pub attempts: u32,
+    /// how many candidates missed each part of the spec
+    pub misses: BTreeMap<SpecMiss, u32>,
+    /// par sheet of the accepted candidate, or of the closest miss
+    pub best: Option<ParSheet>,
+}
+
+impl GeneratorSpec {
+    fn check(&self, sheet: &ParSheet) -> Vec<SpecMiss> {
+        let mut misses = vec![];
+        if (sheet.rtp - self.target_rtp).abs() > self.rtp_tolerance {
+            misses.push(SpecMiss::Rtp);
+        }
+        if sheet.hit_frequency < self.min_hit_frequency {
+            misses.push(SpecMiss::HitFrequency);
+        }
+        if reward_multiple(sheet.max_exposure) > self.max_win as f64 {
+            misses.push(SpecMiss::MaxWin);
+        }
+        let (vol_min, vol_max) = self.volatility;
+        if sheet.volatility_index < vol_min || sheet.volatility_index > vol_max {
+            misses.push(SpecMiss::Volatility);
+        }
+        misses
+    }
+
+    /// How far a candidate is from the spec, 0 = meets it. Only used to pick the closest miss.
+    fn distance(&self, sheet: &ParSheet) -> f64 {
+        let (vol_min, vol_max) = self.volatility;
+        let rtp =
+            ((sheet.rtp - self.target_rtp).abs() - self.rtp_tolerance).max(0.0) / self.target_rtp;
+        let hit = (self.min_hit_frequency - sheet.hit_frequency).max(0.0)
+            / self.min_hit_frequency.max(1e-9);
+        let win = (reward_multiple(sheet.max_exposure) - self.max_win as f64).max(0.0)
+            / self.max_win as f64;
+        let vol = ((vol_min - sheet.volatility_index).max(0.0)
+            + (sheet.volatility_index - vol_max).max(0.0))
+            / vol_min.max(1.0);
+        rtp + hit + win + vol
+    }
+
+    /// Search for a rule set that meets the spec. Every attempt picks a new
+    /// wheel shape and pay table flatness, calibrates the rewards to `target_rtp`
+    /// and is accepted once its par sheet meets every constraint.
+    pub fn generate(&self) -> Result<(RuleSet, GeneratorReport), Box<GeneratorReport>> {
+        assert!(self.volatility.0 <= self.volatility.1);
+        assert!(self.rtp_tolerance > 0.0);
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let mut report = GeneratorReport {
+            spec: self.clone(),
+            attempts: 0,
+            misses: BTreeMap::new(),
+            best: None,
+        };
+        let mut best_distance = f64::INFINITY;
+
+        for _ in 0..self.max_attempts {
+            report.attempts += 1;
+            let shape = RewardShape {
+                prob_var: rng.gen_range(1.2..4.0),
+                reward_exponent: rng.gen_range(0.5..1.0),
+                max_win: self.max_win,
+                scatter: self.scatter,
+                jackpot: self.jackpot,
+            };
+            let rule_set = RuleSet::random_rule_set_from(
+                &mut rng,
+                self.target_rtp,
+                self.wheel_count,
+                &self.wilds,
+                &shape,
+            );
+            if rule_set.validate().is_err() {
+                *report.misses.entry(SpecMiss::Invalid).or_insert(0) += 1;
+                continue;
+            }
+            let sheet = rule_set.par_sheet();
+            let misses = self.check(&sheet);
+            if misses.is_empty() {
+                report.best = Some(sheet);
+                return Ok((rule_set, report));
+            }
+            for miss in misses {
+                *report.misses.entry(miss).or_insert(0) += 1;
+            }
+            let distance = self.distance(&sheet);
+            if distance < best_distance {
+                best_distance = distance;
+                report.best = Some(sheet);
+            }
+        }
+        Err(Box::new(report))
+    }
+}
+
+impl std::fmt::Display for GeneratorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let spec = &self.spec;
+        writeln!(
+            f,
+            "seed: {}  attempts: {}/{}",
+            spec.seed, self.attempts, spec.max_attempts
+        )?;
+        writeln!(
+            f,
+            "spec: rtp {} +- {}, hit_frequency >= {}, max_win <= {}, volatility {}..={}",
+            spec.target_rtp,
+            spec.rtp_tolerance,
+            spec.min_hit_frequency,
+            spec.max_win,
+            spec.volatility.0,
+            spec.volatility.1
+        )?;
+        if let Some(s) = spec.scatter {
+            writeln!(
+                f,
+                "scatter: {} x{}+ gives {} free spins at x{}",
+                s.fruit, s.min_count, s.free_spins, s.win_multiplier
+            )?;
+        }
+        if let Some(j) = spec.jackpot {
+            writeln!(
+                f,
+                "jackpot: {} x{}+ wins the pool, {} bps of every bet",
+                j.fruit, j.count, j.contribution_bps
+            )?;
+        }
+        for (miss, count) in self.misses.iter() {
+            writeln!(f, "missed {miss:?}: {count} times")?;
+        }
+        if let Some(best) = &self.best {
+            writeln!(
+                f,
+                "best: rtp {:.6}, hit_frequency {:.6}, max_win {}, volatility {:.4}",
+                best.rtp,
+                best.hit_frequency,
+                reward_multiple(best.max_exposure),
+                best.volatility_index
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_rule_set() {
+        let spec = GeneratorSpec {
+            seed: 7,
+            ..GeneratorSpec::default()
+        };
+        let (a, report) = spec.generate().unwrap();
+        let (b, _) = spec.generate().unwrap();
+        assert_eq!(a.serialize(), b.serialize());
+
+        let sheet = report.best.unwrap();
+        assert!((sheet.rtp - spec.target_rtp).abs() <= spec.rtp_tolerance);
+        assert!(sheet.hit_frequency >= spec.min_hit_frequency);
+        assert!(reward_multiple(sheet.max_exposure) <= spec.max_win as f64);
+    }
+
+    #[test]
+    fn test_impossible_spec_reports() {
+        let spec = GeneratorSpec {
+            min_hit_frequency: 0.99,
+            max_attempts: 3,
+            ..GeneratorSpec::default()
+        };
+        let report = spec.generate().unwrap_err();
+        assert_eq!(report.attempts, 3);
+        assert_eq!(report.misses[&SpecMiss::HitFrequency], 3);
+        assert!(report.best.is_some());
+    }
+
+    #[test]
+    fn test_scatter_and_jackpot_in_rtp() {
+        let spec = GeneratorSpec {
+            seed: 3,
+            scatter: Some(ScatterRule {
+                fruit: Fruit::bonus,
+                min_count: 2,
+                free_spins: 5,
+                win_multiplier: 2,
+            }),
+            jackpot: Some(JackpotRule {
+                fruit: Fruit::seven,
+                count: 3,
+                contribution_bps: 100,
+            }),
+            ..GeneratorSpec::default()
+        };
+        let (r, report) = spec.generate().unwrap();
+        assert_eq!(r.scatter(), spec.scatter);
+        assert_eq!(r.jackpot(), spec.jackpot);
+        let sheet = report.best.unwrap();
+        assert!(sheet.rtp > sheet.base_rtp + sheet.jackpot_rtp);
+        assert!((sheet.rtp - spec.target_rtp).abs() <= spec.rtp_tolerance);
+
+        let endless = GeneratorSpec {
+            scatter: Some(ScatterRule {
+                fruit: Fruit::bonus,
+                min_count: 1,
+                free_spins: 255,
+                win_multiplier: 1,
+            }),
+            max_attempts: 2,
+            ..spec
+        };
+        let report = endless.generate().unwrap_err();
+        assert_eq!(report.misses[&SpecMiss::Invalid], 2);
+    }
+}
@@ -1,8 +1,38 @@
 #[cfg(feature = "constructors")]
 pub mod generated_rules;
 
+#[cfg(feature = "constructors")]
+pub mod registry;
+
 pub mod rule_set;
 
+pub mod bank;
+pub mod compiled;
+pub mod container;
+pub mod error;
+pub mod symbol;
+
+#[cfg(feature = "formats")]
+pub mod formats;
+
+#[cfg(feature = "generate")]
+pub mod par_sheet;
+
+#[cfg(feature = "generate")]
+pub mod generator;
+
+#[cfg(feature = "generate")]
+pub mod monte_carlo;
+
+#[cfg(feature = "generate")]
+pub mod session;
+
+#[cfg(feature = "generate")]
+pub mod solvency;
+
+#[cfg(feature = "generate")]
+pub mod diff;
+
 include!(concat!(env!("OUT_DIR"), "/get_all_fruits.rs"));
 
 impl Fruit {
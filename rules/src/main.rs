@@ -1,18 +1,437 @@
-use rules::rule_set::RuleSet;
-fn main() {
-    let r = RuleSet::p96();
-    let mut p_v: Vec<_> = r.prob().into_iter().collect();
-    p_v.sort_by_key(|k| k.1);
-    println!("prob: {:?}", p_v);
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rules::formats::Format;
+use rules::generator::GeneratorSpec;
+use rules::registry::RuleSetId;
+use rules::rule_set::{JackpotRule, RuleSet, ScatterRule};
+use rules::session::{BetStrategy, SessionSpec};
+use rules::solvency::SolvencySpec;
+use rules::Fruit;
+
+const USAGE: &str = "usage: rules <command> ...
+
+  generate [--seed N] [--rtp 0.90] [--rtp-tolerance 0.005] [--min-hit 0.15] [--max-win 1000]
+           [--volatility MIN..MAX] [--wheels 3] [--wilds fruit,fruit] [--attempts 200]
+           [--scatter FRUIT,MIN_COUNT,FREE_SPINS,MULTIPLIER] [--jackpot FRUIT,COUNT,BPS]
+           [--out FILE.bin|json|toml]... [--rs FILE] [--name p96]
+                                   search a rule set that meets the spec; its rtp
+                                   counts the free spins and the jackpot contribution
+  simulate RULES N [SEED] [--assert Z]
+                                   Monte Carlo of N paid spins on every core; --assert
+                                   fails if the rtp is over Z std errors from the exact one
+  session  RULES [--bankroll LAMPORTS] [--bet EXP] [--martingale MAX_EXP] [--spins 500]
+           [--stop-loss LAMPORTS] [--stop-win LAMPORTS] [--sessions 10000] [--seed N]
+                                   player sessions: final balances, risk of ruin and
+                                   session length; a bet is 1 << EXP lamports
+  solvency RULES [--bank LAMPORTS] [--bets 20..24] [--players 20] [--rounds 500]
+           [--runs 1000] [--seed N] [--below LAMPORTS]... [--recommend RISK]
+                                   house bank against many players with the program
+                                   payout cap and the client bet limit; --recommend
+                                   finds the smallest bank that caps a payout or stops
+                                   offering the largest bet in at most RISK of the runs
+  analyze  RULES [md|csv] [FILE]   exact RTP report (par sheet)
+  diff     RULES RULES [table|json]  what changed between two rule sets
+  convert  RULES FILE              write RULES as .bin, .json or .toml
+  publish  RULES NAME              make RULES the canonical rule_sets/NAME.toml and write
+                                   what the program and the frontend build from it:
+                                   src/generated_rules/NAME.rs and, for the default
+                                   table, src/default_pacanea_rule_set.bin
+  verify   RULES SEED [--free]     outcome of a spin; SEED is one u16 per wheel
+                                   (1,2,3) or the 32 byte hex random of the program
+
+RULES is a .bin, .json or .toml file, a built in rule set (p90, p94, p96)
+or `default` (the bundled file).";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{msg}\n\n{USAGE}");
+    std::process::exit(2)
+}
+
+fn parse<T: std::str::FromStr>(what: &str, value: Option<String>) -> T {
+    let value = value.unwrap_or_else(|| fail(&format!("{what} needs a value")));
+    value
+        .parse()
+        .unwrap_or_else(|_| fail(&format!("bad value for {what}: {value}")))
+}
+
+fn load(arg: Option<String>) -> RuleSet {
+    let arg = arg.unwrap_or_else(|| fail("missing rule set"));
+    if let Some(id) = RuleSetId::from_name(&arg) {
+        return id.rule_set();
+    }
+    let loaded = match arg.as_str() {
+        "default" => RuleSet::default_internal_deserialize(),
+        path => {
+            let format = Format::from_path(path).unwrap_or_else(|| {
+                fail(&format!(
+                    "{path}: unknown extension, use .bin, .json or .toml"
+                ))
+            });
+            let v = std::fs::read(path).unwrap_or_else(|e| {
+                eprintln!("{path}: {e}");
+                std::process::exit(1)
+            });
+            RuleSet::decode(format, &v)
+        }
+    };
+    loaded.unwrap_or_else(|e| {
+        eprintln!("{arg}: {e}");
+        std::process::exit(1)
+    })
+}
 
-    println!("SEED [0,0,0]: {:?}", r.play_random_from_seed([0, 0, 0]));
-    println!("PROJECTED RETURN: {}", r.projected_return());
+fn save(r: &RuleSet, path: &str) {
+    let format = Format::from_path(path).unwrap_or_else(|| {
+        fail(&format!(
+            "{path}: unknown extension, use .bin, .json or .toml"
+        ))
+    });
+    std::fs::write(path, r.encode(format)).unwrap();
+}
+
+fn generate(mut args: impl Iterator<Item = String>) {
+    let mut spec = GeneratorSpec::default();
+    let mut name = "p96".to_string();
+    let mut outputs = vec![];
+    let mut rs_path = None;
+
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--seed" => spec.seed = parse(&flag, args.next()),
+            "--rtp" => spec.target_rtp = parse(&flag, args.next()),
+            "--rtp-tolerance" => spec.rtp_tolerance = parse(&flag, args.next()),
+            "--min-hit" => spec.min_hit_frequency = parse(&flag, args.next()),
+            "--max-win" => spec.max_win = parse(&flag, args.next()),
+            "--volatility" => {
+                let band: String = parse(&flag, args.next());
+                let (min, max) = band
+                    .split_once("..")
+                    .unwrap_or_else(|| fail(&format!("bad value for {flag}: {band}")));
+                let min = if min.is_empty() {
+                    0.0
+                } else {
+                    parse(&flag, Some(min.to_string()))
+                };
+                let max = if max.is_empty() {
+                    f64::INFINITY
+                } else {
+                    parse(&flag, Some(max.to_string()))
+                };
+                spec.volatility = (min, max);
+            }
+            "--wheels" => spec.wheel_count = parse(&flag, args.next()),
+            "--wilds" => {
+                let wilds: String = parse(&flag, args.next());
+                spec.wilds = wilds
+                    .split(',')
+                    .map(|w| parse::<Fruit>(&flag, Some(w.to_string())))
+                    .collect();
+            }
+            "--scatter" => {
+                let rule: String = parse(&flag, args.next());
+                let parts: Vec<&str> = rule.split(',').collect();
+                let [fruit, min_count, free_spins, win_multiplier] = parts[..] else {
+                    fail(&format!("bad value for {flag}: {rule}"))
+                };
+                spec.scatter = Some(ScatterRule {
+                    fruit: parse(&flag, Some(fruit.to_string())),
+                    min_count: parse(&flag, Some(min_count.to_string())),
+                    free_spins: parse(&flag, Some(free_spins.to_string())),
+                    win_multiplier: parse(&flag, Some(win_multiplier.to_string())),
+                });
+            }
+            "--jackpot" => {
+                let rule: String = parse(&flag, args.next());
+                let parts: Vec<&str> = rule.split(',').collect();
+                let [fruit, count, contribution_bps] = parts[..] else {
+                    fail(&format!("bad value for {flag}: {rule}"))
+                };
+                spec.jackpot = Some(JackpotRule {
+                    fruit: parse(&flag, Some(fruit.to_string())),
+                    count: parse(&flag, Some(count.to_string())),
+                    contribution_bps: parse(&flag, Some(contribution_bps.to_string())),
+                });
+            }
+            "--attempts" => spec.max_attempts = parse(&flag, args.next()),
+            "--name" => name = parse(&flag, args.next()),
+            "--out" => outputs.push(parse::<String>(&flag, args.next())),
+            "--rs" => rs_path = Some(parse::<String>(&flag, args.next())),
+            _ => fail(&format!("unknown argument: {flag}")),
+        }
+    }
+    // the ranges `RuleSet::random_rule_set_from` and `GeneratorSpec::generate` accept
+    if !(0.5..=2.0).contains(&spec.target_rtp) {
+        fail(&format!("--rtp {} must be in 0.5..=2", spec.target_rtp));
+    }
+    if spec.rtp_tolerance.is_nan() || spec.rtp_tolerance <= 0.0 {
+        fail(&format!(
+            "--rtp-tolerance {} must be over 0",
+            spec.rtp_tolerance
+        ));
+    }
+    if spec.max_win <= 3 {
+        fail(&format!("--max-win {} must be over 3", spec.max_win));
+    }
+    if !spec
+        .volatility
+        .0
+        .partial_cmp(&spec.volatility.1)
+        .is_some_and(|o| o.is_le())
+    {
+        fail("--volatility MIN must not be over MAX");
+    }
+    if spec.wheel_count < 2 {
+        fail(&format!("--wheels {} must be at least 2", spec.wheel_count));
+    }
+    if spec.wilds.len() >= Fruit::all().len() {
+        fail("--wilds must leave a fruit that is not wild");
+    }
+
+    let (r, report) = match spec.generate() {
+        Ok(found) => found,
+        Err(report) => {
+            eprintln!("no rule set meets the spec\n{report}");
+            std::process::exit(1)
+        }
+    };
+    eprintln!("{report}");
+    println!("{:#?}", r);
+
+    for path in outputs {
+        save(&r, &path);
+    }
+    if let Some(path) = rs_path {
+        std::fs::write(path, r.rust_constructor(&name)).unwrap();
+    }
+}
+
+fn simulate(mut args: impl Iterator<Item = String>) {
+    let r = load(args.next());
+    let count: u64 = parse("N", args.next());
+    if count == 0 {
+        fail("N must be over 0");
+    }
+    let mut seed = 0;
+    let mut max_z = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--assert" => max_z = Some(parse("--assert", args.next())),
+            _ => seed = parse("SEED", Some(arg)),
+        }
+    }
+
+    let report = r.simulate(count, seed);
+    print!("{report}");
+    if let Some(max_z) = max_z {
+        report.assert_matches_analytic(max_z);
+    }
+}
 
-    assert!(r.play_random_from_seed([0, 0, 0]) == r.play_random_from_seed([0, 0, 0]));
+fn session(mut args: impl Iterator<Item = String>) {
+    let r = load(args.next());
+    let mut spec = SessionSpec::default();
+    let mut bet_exp = None;
+    let mut max_exp = None;
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--bankroll" => spec.bankroll = parse(&flag, args.next()),
+            "--bet" => bet_exp = Some(parse(&flag, args.next())),
+            "--martingale" => max_exp = Some(parse(&flag, args.next())),
+            "--spins" => spec.max_spins = parse(&flag, args.next()),
+            "--stop-loss" => spec.stop_loss = Some(parse(&flag, args.next())),
+            "--stop-win" => spec.stop_win = Some(parse(&flag, args.next())),
+            "--sessions" => spec.sessions = parse(&flag, args.next()),
+            "--seed" => spec.seed = parse(&flag, args.next()),
+            _ => fail(&format!("unknown argument: {flag}")),
+        }
+    }
+    let bet_exp = bet_exp.unwrap_or(match spec.strategy {
+        BetStrategy::Flat { bet_exp } => bet_exp,
+        BetStrategy::Martingale { base_exp, .. } => base_exp,
+    });
+    spec.strategy = match max_exp {
+        Some(max_exp) if max_exp >= bet_exp && max_exp < 63 => BetStrategy::Martingale {
+            base_exp: bet_exp,
+            max_exp,
+        },
+        Some(max_exp) => fail(&format!("--martingale {max_exp} must be in {bet_exp}..63")),
+        None => BetStrategy::Flat { bet_exp },
+    };
+    if bet_exp >= 63 || spec.sessions == 0 {
+        fail("--bet must be under 63 and --sessions over 0");
+    }
+    if spec.bankroll < 1 << bet_exp {
+        fail(&format!(
+            "--bankroll {} can't cover the first bet of {} lamports",
+            spec.bankroll,
+            1u64 << bet_exp
+        ));
+    }
 
-    r.play_monte_carlo(10000);
-    r.play_monte_carlo(30000);
-    r.play_monte_carlo(100000);
-    r.play_monte_carlo(300000);
-    r.play_monte_carlo(1000000);
+    print!("{}", r.simulate_sessions(&spec));
+}
+
+fn solvency(mut args: impl Iterator<Item = String>) {
+    let r = load(args.next());
+    let mut spec = SolvencySpec::default();
+    let mut max_risk = None;
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--bank" => spec.bank = parse(&flag, args.next()),
+            "--bets" => {
+                let range: String = parse(&flag, args.next());
+                let (min, max) = range
+                    .split_once("..")
+                    .unwrap_or_else(|| fail(&format!("bad value for {flag}: {range}")));
+                spec.bet_exps = (
+                    parse(&flag, Some(min.to_string())),
+                    parse(&flag, Some(max.to_string())),
+                );
+            }
+            "--players" => spec.players = parse(&flag, args.next()),
+            "--rounds" => spec.rounds = parse(&flag, args.next()),
+            "--runs" => spec.runs = parse(&flag, args.next()),
+            "--seed" => spec.seed = parse(&flag, args.next()),
+            "--below" => spec.thresholds.push(parse(&flag, args.next())),
+            "--recommend" => max_risk = Some(parse::<f64>(&flag, args.next())),
+            _ => fail(&format!("unknown argument: {flag}")),
+        }
+    }
+    if spec.bet_exps.0 > spec.bet_exps.1 || spec.bet_exps.1 >= 57 {
+        fail("--bets must be MIN..MAX with MIN <= MAX < 57");
+    }
+    if spec.runs == 0 || spec.players == 0 {
+        fail("--runs and --players must be over 0");
+    }
+
+    match max_risk {
+        Some(max_risk) => match r.recommend_bank(&spec, max_risk) {
+            Some(found) => print!("{found}"),
+            None => {
+                eprintln!("no bank keeps the risk under {max_risk}");
+                std::process::exit(1)
+            }
+        },
+        None => print!("{}", r.simulate_solvency(&spec)),
+    }
+}
+
+fn analyze(mut args: impl Iterator<Item = String>) {
+    let r = load(args.next());
+    let format = args.next().unwrap_or("md".to_string());
+    let sheet = r.par_sheet();
+    let text = match format.as_str() {
+        "md" => sheet.to_markdown(),
+        "csv" => sheet.to_csv(),
+        _ => fail(&format!("unknown report format {format:?}, use md or csv")),
+    };
+    match args.next() {
+        Some(path) => std::fs::write(path, text).unwrap(),
+        None => print!("{text}"),
+    }
+}
+
+fn diff(mut args: impl Iterator<Item = String>) {
+    let a = load(args.next());
+    let b = load(args.next());
+    let d = a.diff(&b);
+    match args.next().as_deref() {
+        None | Some("table") => print!("{}", d.to_table()),
+        Some("json") => println!("{}", d.to_json()),
+        Some(format) => fail(&format!(
+            "unknown diff format {format:?}, use table or json"
+        )),
+    }
+}
+
+fn convert(mut args: impl Iterator<Item = String>) {
+    let r = load(args.next());
+    let output: String = parse("output file", args.next());
+    save(&r, &output);
+}
+
+fn publish(mut args: impl Iterator<Item = String>) {
+    let r = load(args.next());
+    let name: String = parse("NAME", args.next());
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        fail(&format!("bad rule set name: {name}"));
+    }
+    save(&r, &format!("rule_sets/{name}.toml"));
+    let rust_path = format!("src/generated_rules/{name}.rs");
+    std::fs::write(&rust_path, r.rust_constructor(&name)).unwrap();
+    // `rust_constructor` does not indent its output, keep the committed files readable
+    let formatted = std::process::Command::new("rustfmt")
+        .args(["--edition", "2021", &rust_path])
+        .status();
+    if !matches!(formatted, Ok(status) if status.success()) {
+        eprintln!("could not run rustfmt on {rust_path}, format it by hand");
+    }
+    if name == RuleSetId::DEFAULT.name() {
+        save(&r, "src/default_pacanea_rule_set.bin");
+    }
+    if RuleSetId::from_name(&name).is_none() {
+        eprintln!("{name} is not in the registry yet, add it to src/registry.rs");
+    }
+    eprintln!("{name}: content hash {:#018x}", r.content_hash());
+}
+
+fn verify(mut args: impl Iterator<Item = String>) {
+    let r = load(args.next());
+    let seed: String = parse("SEED", args.next());
+    let free_spin = match args.next().as_deref() {
+        None => false,
+        Some("--free") => true,
+        Some(flag) => fail(&format!("unknown argument: {flag}")),
+    };
+
+    let wheel_seeds: Vec<u16> = if seed.len() == 64 && !seed.contains(',') {
+        // same as the program: ChaCha8 seeded with the slot hash random gives one u16 per wheel
+        if !seed.bytes().all(|b| b.is_ascii_hexdigit()) {
+            fail(&format!("bad hex random: {seed}"));
+        }
+        let mut random = [0u8; 32];
+        for (i, byte) in random.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&seed[2 * i..2 * i + 2], 16)
+                .unwrap_or_else(|_| fail(&format!("bad hex random: {seed}")));
+        }
+        let mut chacha = ChaCha8Rng::from_seed(random);
+        (0..r.wheel_count()).map(|_| chacha.gen()).collect()
+    } else {
+        seed.split(',')
+            .map(|s| parse("SEED", Some(s.trim().to_string())))
+            .collect()
+    };
+    if wheel_seeds.len() != r.wheel_count() as usize {
+        fail(&format!(
+            "{} wheel seeds for {} wheels",
+            wheel_seeds.len(),
+            r.wheel_count()
+        ));
+    }
+
+    let spin = if free_spin {
+        r.play_free_spin_from_seed(&wheel_seeds)
+    } else {
+        r.play_random_from_seed(&wheel_seeds)
+    };
+    println!("wheel seeds: {wheel_seeds:?}");
+    println!("{spin:#?}");
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let command = args.next().unwrap_or_else(|| fail("missing command"));
+    match command.as_str() {
+        "generate" => generate(args),
+        "simulate" => simulate(args),
+        "session" => session(args),
+        "solvency" => solvency(args),
+        "analyze" => analyze(args),
+        "diff" => diff(args),
+        "convert" => convert(args),
+        "publish" => publish(args),
+        "verify" => verify(args),
+        "-h" | "--help" | "help" => println!("{USAGE}"),
+        _ => fail(&format!("unknown command: {command}")),
+    }
 }
@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::rule_set::{RuleSet, REWARD_ONE};
+
+/// Spins per chunk. Every chunk has its own ChaCha stream, so the result only
+/// depends on the seed and the spin count, not on how many threads ran it.
+const CHUNK_SPINS: u64 = 1 << 16;
+
+/// z-score of the 95% confidence interval
+const Z_95: f64 = 1.96;
+
+/// Result of `RuleSet::simulate`. A spin here is a paid spin together with
+/// every free spin it brings.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct MonteCarloReport {
+    pub seed: u64,
+    pub spins: u64,
+    /// free spins played on top of `spins`
+    pub free_spins: u64,
+    /// average win, as multiple of the bet
+    pub rtp: f64,
+    pub rtp_ci95: (f64, f64),
+    /// standard deviation of the win of one spin
+    pub std_dev: f64,
+    /// part of the spins that won anything
+    pub hit_rate: f64,
+    /// most spins in a row without any win
+    pub longest_losing_streak: u64,
+    /// how many spins won each amount, in basis points of the bet
+    pub histogram: BTreeMap<u64, u64>,
+    /// exact return the simulation should land on: the par sheet rtp without the jackpot,
+    /// the simulation has no pool to pay it from
+    pub analytic_rtp: f64,
+}
+
+/// What one chunk of spins saw, in order, so chunks can be merged.
+#[derive(Debug, Default)]
+struct Chunk {
+    spins: u64,
+    free_spins: u64,
+    sum: f64,
+    sum_sq: f64,
+    hits: u64,
+    /// losses before the first win (all of them if nothing won)
+    leading_losses: u64,
+    /// losses after the last win
+    trailing_losses: u64,
+    longest_losing_streak: u64,
+    histogram: BTreeMap<u64, u64>,
+}
+
+impl Chunk {
+    fn play(rule_set: &RuleSet, seed: u64, index: u64, spins: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        rng.set_stream(index);
+        let mut chunk = Chunk {
+            spins,
+            ..Default::default()
+        };
+        for _ in 0..spins {
+            let (win, free_spins) = rule_set.play_paid_spin_from(&mut rng);
+            chunk.free_spins += free_spins as u64;
+            *chunk.histogram.entry(win).or_insert(0) += 1;
+            if win == 0 {
+                chunk.trailing_losses += 1;
+                if chunk.hits == 0 {
+                    chunk.leading_losses += 1;
+                }
+                chunk.longest_losing_streak =
+                    chunk.longest_losing_streak.max(chunk.trailing_losses);
+            } else {
+                chunk.hits += 1;
+                chunk.trailing_losses = 0;
+                let win = win as f64 / REWARD_ONE as f64;
+                chunk.sum += win;
+                chunk.sum_sq += win * win;
+            }
+        }
+        chunk
+    }
+
+    /// `next` comes right after `self`
+    fn merge(mut self, next: Chunk) -> Chunk {
+        let bridge = self.trailing_losses + next.leading_losses;
+        self.longest_losing_streak = self
+            .longest_losing_streak
+            .max(next.longest_losing_streak)
+            .max(bridge);
+        if self.hits == 0 {
+            self.leading_losses += next.leading_losses;
+        }
+        self.trailing_losses = if next.hits == 0 {
+            bridge
+        } else {
+            next.trailing_losses
+        };
+        self.spins += next.spins;
+        self.free_spins += next.free_spins;
+        self.sum += next.sum;
+        self.sum_sq += next.sum_sq;
+        self.hits += next.hits;
+        for (win, count) in next.histogram {
+            *self.histogram.entry(win).or_insert(0) += count;
+        }
+        self
+    }
+}
+
+impl RuleSet {
+    /// Play `spins` paid spins from `seed` on every core.
+    pub fn simulate(&self, spins: u64, seed: u64) -> MonteCarloReport {
+        assert!(spins > 0);
+        let chunk_count = spins.div_ceil(CHUNK_SPINS) as usize;
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(chunk_count);
+
+        let next = AtomicUsize::new(0);
+        let done = Mutex::new(BTreeMap::new());
+        std::thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= chunk_count {
+                        break;
+                    }
+                    let first = index as u64 * CHUNK_SPINS;
+                    let len = CHUNK_SPINS.min(spins - first);
+                    let chunk = Chunk::play(self, seed, index as u64, len);
+                    done.lock().unwrap().insert(index, chunk);
+                });
+            }
+        });
+        let total = done
+            .into_inner()
+            .unwrap()
+            .into_values()
+            .reduce(Chunk::merge)
+            .unwrap();
+
+        let n = total.spins as f64;
+        let rtp = total.sum / n;
+        let variance = (total.sum_sq / n - rtp * rtp).max(0.0);
+        let std_dev = variance.sqrt();
+        let half_width = Z_95 * std_dev / n.sqrt();
+        let sheet = self.par_sheet();
+        MonteCarloReport {
+            seed,
+            spins: total.spins,
+            free_spins: total.free_spins,
+            rtp,
+            rtp_ci95: (rtp - half_width, rtp + half_width),
+            std_dev,
+            hit_rate: total.hits as f64 / n,
+            longest_losing_streak: total.longest_losing_streak,
+            histogram: total.histogram,
+            analytic_rtp: sheet.rtp - sheet.jackpot_rtp,
+        }
+    }
+}
+
+impl MonteCarloReport {
+    /// how many standard errors the simulated rtp is away from the exact one
+    pub fn deviation_z(&self) -> f64 {
+        let std_err = self.std_dev / (self.spins as f64).sqrt();
+        if std_err == 0.0 {
+            return if self.rtp == self.analytic_rtp {
+                0.0
+            } else {
+                f64::INFINITY
+            };
+        }
+        (self.rtp - self.analytic_rtp).abs() / std_err
+    }
+
+    /// Panics when the simulated rtp is more than `max_z` standard errors away
+    /// from the exact one. 4.0 fails a correct rule set about once in 16000 runs.
+    pub fn assert_matches_analytic(&self, max_z: f64) {
+        let z = self.deviation_z();
+        assert!(
+            z <= max_z,
+            "simulated rtp {:.6} (95% ci {:.6}..{:.6}) is {z:.2} standard errors away from the exact {:.6}",
+            self.rtp,
+            self.rtp_ci95.0,
+            self.rtp_ci95.1,
+            self.analytic_rtp
+        );
+    }
+
+    /// Histogram folded into ranges of win multipliers: (from, to, spins), a win
+    /// lands in `from <= win < to`. `(0, 0)` holds the losses.
+    pub fn histogram_buckets(&self) -> Vec<(u32, u32, u64)> {
+        const EDGES: [u32; 12] = [0, 1, 2, 3, 5, 10, 20, 50, 100, 200, 500, 1000];
+        let mut buckets: Vec<(u32, u32, u64)> = [(0, 0, 0)]
+            .into_iter()
+            .chain(
+                EDGES
+                    .iter()
+                    .zip(EDGES.iter().skip(1).copied().chain([u32::MAX]))
+                    .map(|(lo, hi)| (*lo, hi, 0)),
+            )
+            .collect();
+        for (win, count) in self.histogram.iter() {
+            let bucket = if *win == 0 {
+                &mut buckets[0]
+            } else {
+                let multiple = win / REWARD_ONE as u64;
+                buckets
+                    .iter_mut()
+                    .rev()
+                    .find(|b| b.0 as u64 <= multiple)
+                    .unwrap()
+            };
+            bucket.2 += count;
+        }
+        buckets.retain(|b| b.2 > 0);
+        buckets
+    }
+}
+
+impl std::fmt::Display for MonteCarloReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "seed: {}  spins: {}  free spins: {}",
+            self.seed, self.spins, self.free_spins
+        )?;
+        writeln!(
+            f,
+            "rtp: {:.6}  95% ci: {:.6}..{:.6}  exact: {:.6}  ({:.2} std errors)",
+            self.rtp,
+            self.rtp_ci95.0,
+            self.rtp_ci95.1,
+            self.analytic_rtp,
+            self.deviation_z()
+        )?;
+        writeln!(
+            f,
+            "std_dev: {:.4}  hit_rate: {:.6}",
+            self.std_dev, self.hit_rate
+        )?;
+        writeln!(f, "longest_losing_streak: {}", self.longest_losing_streak)?;
+        writeln!(f, "| win | spins | share |\n|---|---|---|")?;
+        for (lo, hi, count) in self.histogram_buckets() {
+            let win = match (lo, hi) {
+                (lo, hi) if lo == hi => format!("{lo}"),
+                (lo, u32::MAX) => format!("{lo}+"),
+                (lo, hi) => format!("{lo}..{hi}"),
+            };
+            writeln!(
+                f,
+                "| {win} | {count} | {:.6} |",
+                count as f64 / self.spins as f64
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rule_set::{RewardShape, ScatterRule};
+    use crate::Fruit;
+
+    #[test]
+    fn test_merge_losing_streaks() {
+        let chunk = |wins: &[u32]| {
+            let mut c = Chunk::default();
+            for win in wins {
+                c = c.merge(Chunk {
+                    spins: 1,
+                    hits: (*win > 0) as u64,
+                    leading_losses: (*win == 0) as u64,
+                    trailing_losses: (*win == 0) as u64,
+                    longest_losing_streak: (*win == 0) as u64,
+                    ..Default::default()
+                });
+            }
+            c
+        };
+        let merged = chunk(&[0, 0, 3, 0])
+            .merge(chunk(&[0, 0]))
+            .merge(chunk(&[0, 1, 0]));
+        assert_eq!(merged.longest_losing_streak, 4);
+        assert_eq!(merged.leading_losses, 2);
+        assert_eq!(merged.trailing_losses, 1);
+        assert_eq!(merged.hits, 2);
+        assert_eq!(merged.spins, 9);
+    }
+
+    #[test]
+    fn test_simulate_matches_exact() {
+        let mut r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(9),
+            0.9,
+            3,
+            &[],
+            &RewardShape::default(),
+        );
+        r.set_scatter(Some(ScatterRule {
+            fruit: Fruit::bell,
+            min_count: 2,
+            free_spins: 2,
+            win_multiplier: 2,
+        }));
+        let report = r.simulate(CHUNK_SPINS * 3 + 123, 11);
+        assert_eq!(report.spins, CHUNK_SPINS * 3 + 123);
+        assert_eq!(report.histogram.values().sum::<u64>(), report.spins);
+        assert!(report.free_spins > 0);
+        report.assert_matches_analytic(4.0);
+
+        assert_eq!(report, r.simulate(CHUNK_SPINS * 3 + 123, 11));
+    }
+}
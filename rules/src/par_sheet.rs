@@ -0,0 +1,452 @@
+use std::collections::BTreeMap;
+
+use crate::rule_set::{reel_weights, reward_multiple, uncapped_reward, RuleSet, REWARD_ONE};
+use crate::Fruit;
+
+/// z-score used for the volatility index (90% confidence)
+const VOLATILITY_Z: f64 = 1.645;
+
+/// Exact payout report, computed over every possible stop of every wheel.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ParSheet {
+    pub wheel_count: u8,
+    /// number of distinct outcomes (fruit multisets) that can show up
+    pub outcome_count: usize,
+    /// return to player, as multiple of the bet, free spins included; infinite when
+    /// free spins never end
+    pub rtp: f64,
+    /// return to player of the paid spin alone
+    pub base_rtp: f64,
+    /// chance that a spin wins free spins
+    pub free_spin_frequency: f64,
+    /// free spins played on average for every paid spin, retriggers included
+    pub free_spins_per_spin: f64,
+    /// chance that a spin wins the jackpot pool
+    pub jackpot_frequency: f64,
+    /// part of `rtp` paid back by the jackpot pool, the bet contribution
+    pub jackpot_rtp: f64,
+    /// chance that a spin pays anything
+    pub hit_frequency: f64,
+    /// variance of the paid spin win, in bets squared
+    pub variance: f64,
+    /// VOLATILITY_Z * standard deviation of the win
+    pub volatility_index: f64,
+    /// biggest win that can happen, in basis points of the bet
+    pub max_exposure: u32,
+    /// chance that a paid spin wins more than `max_win` and is cut down to it
+    pub max_win_frequency: f64,
+    /// part of the return `max_win` takes away, in paid and free spins
+    pub max_win_rtp_loss: f64,
+    pub combos: Vec<ParSheetCombo>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ParSheetCombo {
+    pub fruit: Fruit,
+    pub count: u8,
+    /// in basis points of the bet
+    pub reward: u32,
+    /// chance that this combo pays on a spin
+    pub probability: f64,
+    /// part of `rtp` paid by this combo, in paid and free spins
+    pub rtp_contribution: f64,
+    /// rtp_contribution / rtp
+    pub rtp_share: f64,
+}
+
+/// Probability of every multiset of fruits (sorted) that the wheels can show.
+/// Payouts only depend on how many times each fruit hits, so the wheels
+/// are folded one at a time instead of enumerating every wheel stop.
+pub fn outcome_distribution(rule_set: &RuleSet) -> BTreeMap<Vec<Fruit>, f64> {
+    let mut dist = BTreeMap::from([(vec![], 1.0)]);
+    for reel in rule_set.reels() {
+        let weights = reel_weights(reel);
+        let mut next = BTreeMap::new();
+        for (outcome, p) in dist.iter() {
+            for (fruit, w) in weights.iter() {
+                let mut o: Vec<Fruit> = outcome.clone();
+                let pos = o.partition_point(|f| f <= fruit);
+                o.insert(pos, *fruit);
+                *next.entry(o).or_insert(0.0) += p * w;
+            }
+        }
+        dist = next;
+    }
+    dist
+}
+
+impl RuleSet {
+    pub fn par_sheet(&self) -> ParSheet {
+        self.par_sheet_over(&outcome_distribution(self))
+    }
+
+    /// `dist` is `outcome_distribution` of a rule set with the same wheels.
+    /// Every spin, paid or free, wins `F` free spins with chance `T`, so a paid spin
+    /// brings `S = T*F + S*T*F = T*F / (1 - T*F)` free spins on average.
+    pub(crate) fn par_sheet_over(&self, dist: &BTreeMap<Vec<Fruit>, f64>) -> ParSheet {
+        let multiplier = self.scatter.map(|s| s.win_multiplier).unwrap_or(1) as f64;
+        let mut base_rtp = 0.0;
+        let mut free_rtp = 0.0;
+        let mut second_moment = 0.0;
+        let mut hit_frequency = 0.0;
+        let mut free_spin_frequency = 0.0;
+        let mut free_spins_per_trigger = 0.0;
+        let mut jackpot_frequency = 0.0;
+        let mut max_exposure = 0;
+        let mut max_win_frequency = 0.0;
+        let mut base_loss = 0.0;
+        let mut free_loss = 0.0;
+        let mut combo_prob = BTreeMap::<(Fruit, u8), f64>::new();
+        let mut combo_ev = BTreeMap::<(Fruit, u8), f64>::new();
+        let mut combo_ev_free = BTreeMap::<(Fruit, u8), f64>::new();
+
+        for (outcome, p) in dist.iter() {
+            let free_spins = self.free_spins_won(outcome);
+            if free_spins > 0 {
+                free_spin_frequency += p;
+                free_spins_per_trigger += p * free_spins as f64;
+            }
+            if self.jackpot_won(outcome) {
+                jackpot_frequency += p;
+            }
+
+            let matched = self.matched_rewards(outcome);
+            let uncapped = uncapped_reward(&matched);
+            if uncapped == 0 {
+                continue;
+            }
+            let win = uncapped.min(self.max_win as u64) as u32;
+            let win_f = reward_multiple(win);
+            let max_win_f = reward_multiple(self.max_win);
+            let uncapped_f = uncapped as f64 / REWARD_ONE as f64;
+            let free_win_f = (uncapped_f * multiplier).min(max_win_f);
+            if uncapped > win as u64 {
+                max_win_frequency += p;
+            }
+            base_loss += p * (uncapped_f - win_f);
+            free_loss += p * (uncapped_f * multiplier - free_win_f);
+            base_rtp += p * win_f;
+            free_rtp += p * free_win_f;
+            second_moment += p * win_f * win_f;
+            hit_frequency += p;
+            max_exposure = max_exposure.max(win);
+
+            // if the win was clamped, every combo gets its part of the clamped win
+            let raw: f64 = matched.iter().map(|(_, r)| *r as f64).sum();
+            for (combo, reward) in matched.iter() {
+                let part = *reward as f64 / raw;
+                *combo_prob.entry(*combo).or_insert(0.0) += p;
+                *combo_ev.entry(*combo).or_insert(0.0) += p * win_f * part;
+                *combo_ev_free.entry(*combo).or_insert(0.0) += p * free_win_f * part;
+            }
+        }
+
+        // free_spins_per_trigger already has the T factor in it; `validate` refuses
+        // rule sets where free spins never end, their return is infinite
+        let free_spins_per_spin = if free_spins_per_trigger < 1.0 {
+            free_spins_per_trigger / (1.0 - free_spins_per_trigger)
+        } else {
+            f64::INFINITY
+        };
+        // in the long run the pool pays back everything that goes into it
+        let jackpot_rtp = self
+            .jackpot
+            .map(|j| j.contribution_bps as f64 / 10000.0)
+            .unwrap_or(0.0);
+        let rtp = base_rtp + free_spins_per_spin * free_rtp + jackpot_rtp;
+
+        let variance = second_moment - base_rtp * base_rtp;
+        let combos = self
+            .rewards
+            .iter()
+            .map(|((fruit, count), reward)| {
+                let key = (*fruit, *count);
+                let rtp_contribution = *combo_ev.get(&key).unwrap_or(&0.0)
+                    + free_spins_per_spin * *combo_ev_free.get(&key).unwrap_or(&0.0);
+                ParSheetCombo {
+                    fruit: *fruit,
+                    count: *count,
+                    reward: *reward,
+                    probability: *combo_prob.get(&key).unwrap_or(&0.0),
+                    rtp_contribution,
+                    rtp_share: if rtp > 0.0 {
+                        rtp_contribution / rtp
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect();
+
+        ParSheet {
+            wheel_count: self.wheel_count,
+            outcome_count: dist.len(),
+            rtp,
+            base_rtp,
+            free_spin_frequency,
+            free_spins_per_spin,
+            jackpot_frequency,
+            jackpot_rtp,
+            hit_frequency,
+            variance,
+            volatility_index: VOLATILITY_Z * variance.sqrt(),
+            max_exposure,
+            max_win_frequency,
+            max_win_rtp_loss: base_loss + free_spins_per_spin * free_loss,
+            combos,
+        }
+    }
+
+    /// exact return to player; `projected_return` treats every combo as independent
+    pub fn exact_return(&self) -> f64 {
+        self.par_sheet().rtp
+    }
+}
+
+impl ParSheet {
+    fn summary_rows(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("wheel_count", format!("{}", self.wheel_count)),
+            ("outcome_count", format!("{}", self.outcome_count)),
+            ("rtp", format!("{:.6}", self.rtp)),
+            ("base_rtp", format!("{:.6}", self.base_rtp)),
+            (
+                "free_spin_frequency",
+                format!("{:.6}", self.free_spin_frequency),
+            ),
+            (
+                "free_spins_per_spin",
+                format!("{:.6}", self.free_spins_per_spin),
+            ),
+            (
+                "jackpot_frequency",
+                format!("{:.8}", self.jackpot_frequency),
+            ),
+            ("jackpot_rtp", format!("{:.6}", self.jackpot_rtp)),
+            ("hit_frequency", format!("{:.6}", self.hit_frequency)),
+            ("variance", format!("{:.4}", self.variance)),
+            ("volatility_index", format!("{:.4}", self.volatility_index)),
+            (
+                "max_exposure",
+                format!("{}", reward_multiple(self.max_exposure)),
+            ),
+            (
+                "max_win_frequency",
+                format!("{:.8}", self.max_win_frequency),
+            ),
+            ("max_win_rtp_loss", format!("{:.6}", self.max_win_rtp_loss)),
+        ]
+    }
+
+    fn combo_rows(&self) -> Vec<[String; 7]> {
+        self.combos
+            .iter()
+            .map(|c| {
+                let hit_every = if c.probability > 0.0 {
+                    format!("{:.1}", 1.0 / c.probability)
+                } else {
+                    "never".to_string()
+                };
+                [
+                    c.fruit.to_link_str().to_string(),
+                    format!("{}", c.count),
+                    format!("{}", reward_multiple(c.reward)),
+                    format!("{:.8}", c.probability),
+                    hit_every,
+                    format!("{:.6}", c.rtp_contribution),
+                    format!("{:.4}", c.rtp_share),
+                ]
+            })
+            .collect()
+    }
+
+    const COMBO_HEADER: [&'static str; 7] = [
+        "fruit",
+        "count",
+        "reward",
+        "probability",
+        "hit_every",
+        "rtp_contribution",
+        "rtp_share",
+    ];
+
+    /// summary block, empty line, then one row per combo
+    pub fn to_csv(&self) -> String {
+        let mut csv = "metric,value\n".to_string();
+        for (k, v) in self.summary_rows() {
+            csv.push_str(&format!("{k},{v}\n"));
+        }
+        csv.push('\n');
+        csv.push_str(&Self::COMBO_HEADER.join(","));
+        csv.push('\n');
+        for row in self.combo_rows() {
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = "| metric | value |\n|---|---|\n".to_string();
+        for (k, v) in self.summary_rows() {
+            md.push_str(&format!("| {k} | {v} |\n"));
+        }
+        md.push('\n');
+        md.push_str(&format!("| {} |\n", Self::COMBO_HEADER.join(" | ")));
+        md.push_str(&format!("|{}\n", "---|".repeat(Self::COMBO_HEADER.len())));
+        for row in self.combo_rows() {
+            md.push_str(&format!("| {} |\n", row.join(" | ")));
+        }
+        md
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rule_set::{AppliedCap, JackpotRule, ScatterRule};
+
+    fn small_rule_set() -> RuleSet {
+        RuleSet {
+            prob: BTreeMap::from([
+                (Fruit::bananas, 32768),
+                (Fruit::bell, 16384),
+                (Fruit::watermelon, 16383),
+            ]),
+            rewards: BTreeMap::from([((Fruit::bananas, 3), 100_000), ((Fruit::bell, 2), 20_000)]),
+            wheel_count: 3,
+            reel_prob: vec![],
+            wilds: Default::default(),
+            scatter: None,
+            jackpot: None,
+            max_win: 1000 * REWARD_ONE,
+        }
+    }
+
+    #[test]
+    fn test_reel_weights_match_seeds() {
+        let r = small_rule_set();
+        let mut hits = BTreeMap::<Fruit, u32>::new();
+        for seed in 0..=u16::MAX {
+            let fruit = r.play_random_from_seed(&[seed, 0, 0]).symbols[0];
+            *hits.entry(fruit).or_insert(0) += 1;
+        }
+        for (fruit, w) in reel_weights(&r.prob) {
+            assert_eq!(hits[&fruit] as f64 / 65536.0, w);
+        }
+    }
+
+    #[test]
+    fn test_exact_return_small() {
+        let r = small_rule_set();
+        let w_banana: f64 = 32769.0 / 65536.0;
+        let w_bell: f64 = 16384.0 / 65536.0;
+        let expected = 10.0 * w_banana.powi(3) + 2.0 * 3.0 * w_bell.powi(2) * (1.0 - w_bell);
+
+        let sheet = r.par_sheet();
+        assert!((sheet.rtp - expected).abs() < 1e-12);
+        assert_eq!(sheet.max_exposure, 100_000);
+        let total_share: f64 = sheet.combos.iter().map(|c| c.rtp_share).sum();
+        assert!((total_share - 1.0).abs() < 1e-12);
+        let total_p: f64 = outcome_distribution(&r).values().sum();
+        assert!((total_p - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_fractional_reward() {
+        let mut r = small_rule_set();
+        r.rewards.insert((Fruit::bell, 2), 15_000);
+        let w_banana: f64 = 32769.0 / 65536.0;
+        let w_bell: f64 = 16384.0 / 65536.0;
+        let expected = 10.0 * w_banana.powi(3) + 1.5 * 3.0 * w_bell.powi(2) * (1.0 - w_bell);
+        assert!((r.exact_return() - expected).abs() < 1e-12);
+        assert_eq!(
+            r.play_random_from_seed(&[u16::MAX, 40000, 40000]).reward,
+            15_000
+        );
+    }
+
+    #[test]
+    fn test_max_win() {
+        let mut r = small_rule_set();
+        r.rewards.insert((Fruit::bananas, 3), 40_000);
+        r.rewards.insert((Fruit::watermelon, 1), 30_000);
+        let base = r.par_sheet();
+        assert_eq!(base.max_win_frequency, 0.0);
+        assert_eq!(base.max_exposure, 50_000);
+
+        // 2 bells and a watermelon pay 5x, over the cap
+        r.set_max_win(40_000);
+        let w_bell: f64 = 16384.0 / 65536.0;
+        let w_watermelon: f64 = 16383.0 / 65536.0;
+        let capped = 3.0 * w_bell.powi(2) * w_watermelon;
+        let sheet = r.par_sheet();
+        assert!((sheet.max_win_frequency - capped).abs() < 1e-12);
+        assert!((sheet.max_win_rtp_loss - capped).abs() < 1e-12);
+        assert!((base.rtp - sheet.rtp - capped).abs() < 1e-12);
+        assert_eq!(sheet.max_exposure, 40_000);
+
+        let spin = r.play_random_from_seed(&[40000, 40000, u16::MAX]);
+        assert_eq!(spin.reward, 40_000);
+        assert_eq!(
+            spin.caps,
+            vec![AppliedCap::MaxWin {
+                uncapped: 50_000,
+                cap: 40_000
+            }]
+        );
+    }
+
+    #[test]
+    fn test_exact_return_free_spins() {
+        let mut r = small_rule_set();
+        let base = r.exact_return();
+        r.set_scatter(Some(ScatterRule {
+            fruit: Fruit::bell,
+            min_count: 2,
+            free_spins: 1,
+            win_multiplier: 2,
+        }));
+        let w_bell: f64 = 16384.0 / 65536.0;
+        let trigger = 3.0 * w_bell.powi(2) * (1.0 - w_bell) + w_bell.powi(3);
+        let free_spins_per_spin = trigger / (1.0 - trigger);
+
+        let sheet = r.par_sheet();
+        assert!((sheet.free_spin_frequency - trigger).abs() < 1e-12);
+        assert!((sheet.free_spins_per_spin - free_spins_per_spin).abs() < 1e-12);
+        assert!((sheet.rtp - base * (1.0 + 2.0 * free_spins_per_spin)).abs() < 1e-12);
+        let total_share: f64 = sheet.combos.iter().map(|c| c.rtp_share).sum();
+        assert!((total_share - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_endless_free_spins() {
+        let mut r = small_rule_set();
+        r.set_scatter(Some(ScatterRule {
+            fruit: Fruit::bananas,
+            min_count: 1,
+            free_spins: 2,
+            win_multiplier: 1,
+        }));
+        assert_eq!(r.par_sheet().rtp, f64::INFINITY);
+        assert_eq!(r.return_bound(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_jackpot() {
+        let mut r = small_rule_set();
+        let base = r.exact_return();
+        r.set_jackpot(Some(JackpotRule {
+            fruit: Fruit::bananas,
+            count: 3,
+            contribution_bps: 100,
+        }));
+        let w_banana: f64 = 32769.0 / 65536.0;
+
+        let sheet = r.par_sheet();
+        assert!((sheet.jackpot_frequency - w_banana.powi(3)).abs() < 1e-12);
+        assert!((sheet.rtp - base - 0.01).abs() < 1e-12);
+        assert!(r.play_random_from_seed(&[0, 0, 0]).jackpot);
+        assert!(!r.play_random_from_seed(&[0, 0, u16::MAX]).jackpot);
+    }
+}
@@ -0,0 +1,138 @@
+//! Every rule set the house offers, by a stable id. The program and the frontend
+//! only pass the id around; the rules behind it are built into both.
+
+use crate::compiled::CompiledRuleSet;
+use crate::generated_rules::{p90, p94, p96};
+use crate::rule_set::RuleSet;
+
+/// Id of a table. The value goes into instructions and accounts, never reuse one.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[repr(u8)]
+pub enum RuleSetId {
+    P90 = 90,
+    P94 = 94,
+    P96 = 96,
+}
+
+impl RuleSetId {
+    pub const ALL: [RuleSetId; 3] = [RuleSetId::P90, RuleSetId::P94, RuleSetId::P96];
+    /// played when no id is given
+    pub const DEFAULT: RuleSetId = RuleSetId::P96;
+    /// most wheels of any table, for buffers sized at compile time
+    pub const MAX_WHEELS: usize = {
+        let mut max = 0;
+        let mut i = 0;
+        while i < Self::ALL.len() {
+            let wheels = Self::ALL[i].compiled().wheel_count as usize;
+            if wheels > max {
+                max = wheels;
+            }
+            i += 1;
+        }
+        max
+    };
+
+    pub const fn from_u8(id: u8) -> Option<Self> {
+        match id {
+            90 => Some(RuleSetId::P90),
+            94 => Some(RuleSetId::P94),
+            96 => Some(RuleSetId::P96),
+            _ => None,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|id| id.name() == name)
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            RuleSetId::P90 => "p90",
+            RuleSetId::P94 => "p94",
+            RuleSetId::P96 => "p96",
+        }
+    }
+
+    pub fn rule_set(&self) -> RuleSet {
+        match self {
+            RuleSetId::P90 => RuleSet::p90(),
+            RuleSetId::P94 => RuleSet::p94(),
+            RuleSetId::P96 => RuleSet::p96(),
+        }
+    }
+
+    pub const fn compiled(&self) -> CompiledRuleSet<'static> {
+        match self {
+            RuleSetId::P90 => p90::P90,
+            RuleSetId::P94 => p94::P94,
+            RuleSetId::P96 => p96::P96,
+        }
+    }
+
+    /// `RuleSet::content_hash` of the table, fixed when it was generated
+    pub const fn content_hash(&self) -> u64 {
+        match self {
+            RuleSetId::P90 => p90::P90_HASH,
+            RuleSetId::P94 => p94::P94_HASH,
+            RuleSetId::P96 => p96::P96_HASH,
+        }
+    }
+}
+
+impl std::fmt::Display for RuleSetId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        for id in RuleSetId::ALL {
+            assert_eq!(RuleSetId::from_u8(id as u8), Some(id));
+            assert_eq!(RuleSetId::from_name(id.name()), Some(id));
+            let r = id.rule_set();
+            assert_eq!(r.validate(), Ok(()));
+            assert_eq!(
+                r.content_hash(),
+                id.content_hash(),
+                "generated_rules/{id}.rs was edited after it was published"
+            );
+            let (cdf, rewards) = (r.cdf_table(), r.reward_table());
+            assert_eq!(r.compiled(&cdf, &rewards), id.compiled(), "{id}");
+            assert!(r.wheel_count() as usize <= RuleSetId::MAX_WHEELS);
+        }
+        assert_eq!(RuleSetId::from_u8(0), None);
+    }
+
+    /// `rule_sets/NAME.toml` is what `rules publish` wrote the table from
+    #[cfg(feature = "formats")]
+    #[test]
+    fn test_canonical_files() {
+        for id in RuleSetId::ALL {
+            let path = format!("{}/rule_sets/{id}.toml", env!("CARGO_MANIFEST_DIR"));
+            let text = std::fs::read_to_string(&path).unwrap();
+            let r = RuleSet::from_toml(&text).unwrap();
+            assert_eq!(
+                r.content_hash(),
+                id.content_hash(),
+                "{path} and generated_rules/{id}.rs differ, run `rules publish {path} {id}`"
+            );
+        }
+    }
+
+    #[cfg(feature = "generate")]
+    #[test]
+    fn test_rtp_matches_name() {
+        for id in RuleSetId::ALL {
+            let rtp = id.rule_set().par_sheet().rtp;
+            let named = id.name()[1..].parse::<f64>().unwrap() / 100.0;
+            assert!((rtp - named).abs() < 0.005, "{id}: {rtp}");
+        }
+    }
+}
@@ -1,23 +1,131 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
+use crate::error::RuleSetError;
 use crate::Fruit;
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+/// Rewards are fixed point, in basis points of the bet: `REWARD_ONE` gives the bet back,
+/// 15000 pays 1.5x.
+pub const REWARD_ONE: u32 = 10_000;
+
+/// reward as multiple of the bet
+pub fn reward_multiple(reward: u32) -> f64 {
+    reward as f64 / REWARD_ONE as f64
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct RuleSet {
     pub(crate) prob: BTreeMap<Fruit, u16>,
-    pub(crate) rewards: BTreeMap<(Fruit, u8), u16>,
+    /// in basis points of the bet, see `REWARD_ONE`
+    pub(crate) rewards: BTreeMap<(Fruit, u8), u32>,
     pub(crate) wheel_count: u8,
+    /// per wheel probability tables; empty = every wheel uses `prob`
+    pub(crate) reel_prob: Vec<BTreeMap<Fruit, u16>>,
+    /// fruits that can stand in for any other fruit
+    pub(crate) wilds: BTreeSet<Fruit>,
+    pub(crate) scatter: Option<ScatterRule>,
+    pub(crate) jackpot: Option<JackpotRule>,
+    /// no spin pays more than this, in basis points of the bet; free spins
+    /// multiply first and are capped after
+    pub(crate) max_win: u32,
+}
+
+/// `min_count` or more `fruit` anywhere on the wheels give `free_spins` more spins,
+/// paid with `win_multiplier` times the normal win. Wilds do not count as scatter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ScatterRule {
+    pub fruit: Fruit,
+    pub min_count: u8,
+    pub free_spins: u8,
+    pub win_multiplier: u8,
+}
+
+/// `contribution_bps` / 10000 of every paid bet goes to the jackpot pool,
+/// `count` or more `fruit` on the wheels win the whole pool. Wilds do not count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct JackpotRule {
+    pub fruit: Fruit,
+    pub count: u8,
+    pub contribution_bps: u16,
+}
+
+/// Everything that happened on one spin, and why it paid what it paid.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SpinOutcome {
+    /// one per wheel, in wheel order
+    pub symbols: Vec<Fruit>,
+    /// where each wheel stopped on its virtual reel of 2^16 stops; the seed of that wheel
+    pub stops: Vec<u16>,
+    /// the `(fruit, count)` rules that paid, wilds already counted in
+    pub matched: Vec<MatchedRule>,
+    /// the matched rewards are multiplied by this, 1 unless it is a free spin
+    pub multiplier: u8,
+    /// win, in basis points of the bet, after `caps`
+    pub reward: u32,
+    /// free spins won by the scatter
+    pub free_spins: u8,
+    /// the jackpot pool is won, on top of `reward`
+    pub jackpot: bool,
+    /// limits that cut the win down, in the order they were applied
+    pub caps: Vec<AppliedCap>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MatchedRule {
+    pub fruit: Fruit,
+    pub count: u8,
+    /// in basis points of the bet
+    pub reward: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum AppliedCap {
+    /// the biggest win a spin can pay, in basis points of the bet
+    MaxWin { uncapped: u64, cap: u32 },
+    /// the program never pays more than the bank can afford, in lamports
+    Bank { win: u64, paid: u64 },
+}
+
+/// Knobs for the shape of a random rule set, see `RuleSet::random_rule_set_from`.
+#[cfg(feature = "generate")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardShape {
+    /// how many times more probable the most common fruit is than the rarest one
+    pub prob_var: f64,
+    /// rewards are `(fair reward) ^ reward_exponent`; lower = flatter pay table
+    pub reward_exponent: f64,
+    /// no spin pays more than this, as multiple of the bet; becomes `RuleSet::max_win`
+    pub max_win: u16,
+    /// the rewards are calibrated with these in, so free spins and the jackpot
+    /// pool are part of `desired_pay`
+    pub scatter: Option<ScatterRule>,
+    pub jackpot: Option<JackpotRule>,
+}
+
+#[cfg(feature = "generate")]
+impl Default for RewardShape {
+    fn default() -> Self {
+        RewardShape {
+            prob_var: 2.0,
+            reward_exponent: 0.8,
+            max_win: 55666,
+            scatter: None,
+            jackpot: None,
+        }
+    }
 }
 
 #[cfg(feature = "generate")]
 pub fn make_random_prob_space(len: usize) -> Vec<u16> {
+    make_random_prob_space_from(&mut rand::thread_rng(), len, 2.0)
+}
+
+/// `prob_var` = how many times more probable one chip is as to another
+#[cfg(feature = "generate")]
+pub fn make_random_prob_space_from(r: &mut impl rand::Rng, len: usize, prob_var: f64) -> Vec<u16> {
     assert!(len > 2);
-    use rand::Rng;
+    assert!(prob_var > 1.0);
 
     // pick random values but keep min,max
-    let mut r = rand::thread_rng();
-    // prob_var = how many times more probable one chip is as to another
-    let prob_var = 2.0;
     let mut some_prob = vec![];
     for _ in 0..(len - 2) {
         some_prob.push(r.gen_range(1.0..prob_var));
@@ -33,9 +141,9 @@ pub fn make_random_prob_space(len: usize) -> Vec<u16> {
     }
     let mut some_prob: Vec<u16> = some_prob
         .into_iter()
-        .map(|x| (x * std::u16::MAX as f64) as u16)
+        .map(|x| (x * u16::MAX as f64) as u16)
         .collect();
-    let pre_sum = std::u16::MAX - some_prob.iter().sum::<u16>();
+    let pre_sum = u16::MAX - some_prob.iter().sum::<u16>();
     for _ in 0..pre_sum {
         let i = r.gen_range(0..some_prob.len());
         some_prob[i] += 1;
@@ -43,7 +151,7 @@ pub fn make_random_prob_space(len: usize) -> Vec<u16> {
     some_prob.sort();
     some_prob.reverse();
 
-    assert_eq!(some_prob.iter().sum::<u16>(), std::u16::MAX);
+    assert_eq!(some_prob.iter().sum::<u16>(), u16::MAX);
 
     some_prob
 }
@@ -54,160 +162,386 @@ fn get_random_index_per_density(mut rand_val: u16, prob: &BTreeMap<Fruit, u16>)
     use strum::VariantArray;
 
     for i in Fruit::all().iter() {
-        if rand_val <= prob[i] {
+        // fruits missing from a wheel never show up on it
+        let p = *prob.get(i).unwrap_or(&0);
+        if p == 0 {
+            continue;
+        }
+        if rand_val <= p {
             return *i;
         }
-        rand_val -= prob[i];
+        rand_val -= p;
     }
-    return Fruit::VARIANTS[0];
+    Fruit::VARIANTS[0]
 }
 
-/// get_prob(p, "banana", 1, 3) = prob that banana hits 1 time = p(banana, x, x) + p(x, banana, x) + p(x, x, banana), x != banana
-///     p(x) = 1 - prob[fruit]
-///     p("banana") = prob[fruit]
-///  p(banana, 1, 3) = C(3, 1) * p(banana)^1 * p(x)^2
-///  p(banana, 2, 3) = C(3, 2) * p(banana)^2 * p(x)^1
-///  p(banana, 3, 3) = C(3, 3) * p(banana)^3 * p(x)^0
-/// get_prob(p, "banana", 2, 3)
-/// get_prob(p, "banana", 3, 3)
-#[cfg(feature = "generate")]
-fn get_prob_for_index_and_density(
-    prob: &BTreeMap<Fruit, u16>,
-    fruit: Fruit,
-    count: u8,
-    total: u8,
-) -> f64 {
-    assert!(count > 0);
-    assert!(count <= total);
-    assert!(total > 1);
-
-    let p_fruit = prob[&fruit] as f64 / std::u16::MAX as f64;
-    let p_x = 1.0 - p_fruit;
-
-    let comb = combinari(total, count) as f64;
+fn count_fruit_hits(fruits: &[Fruit]) -> BTreeMap<Fruit, u8> {
+    let mut fruit_hits = BTreeMap::new();
+    for x in fruits.iter() {
+        *fruit_hits.entry(*x).or_insert(0) += 1;
+    }
+    fruit_hits
+}
 
-    comb * p_fruit.powi(count as i32) * p_x.powi(total as i32 - count as i32)
+/// sum of the matched rewards, before `max_win`
+pub(crate) fn uncapped_reward(matched: &[((Fruit, u8), u32)]) -> u64 {
+    matched.iter().map(|(_, r)| *r as u64).sum()
 }
 
+/// exact chance of every fruit on one wheel, `(fruit, hit seeds / 2^16)`.
+/// the first fruit gets one extra seed, see `get_random_index_per_density`
 #[cfg(feature = "generate")]
-fn factorial(n: u8) -> u64 {
-    let mut t = 1_u64;
-    for i in 1..=n {
-        t *= i as u64;
+pub(crate) fn reel_weights(prob: &BTreeMap<Fruit, u16>) -> Vec<(Fruit, f64)> {
+    let seed_count = u16::MAX as f64 + 1.0;
+    let mut weights = vec![];
+    for i in Fruit::all().iter() {
+        let p = *prob.get(i).unwrap_or(&0);
+        if p == 0 {
+            continue;
+        }
+        let extra = if weights.is_empty() { 1.0 } else { 0.0 };
+        let seeds = p as f64 + extra;
+        weights.push((*i, seeds / seed_count));
     }
-    t
+    weights
 }
+
+/// get_prob(reels, "banana", 1) = prob that banana hits 1 time = p0(banana, x, x) + p1(x, banana, x) + p2(x, x, banana), x != banana
+///     pi("banana") = reels[i][fruit]
+///     pi(x) = 1 - reels[i][fruit]
+/// When all wheels share one table this is the binomial C(n, k) * p^k * (1-p)^(n-k);
+/// with a table per wheel we count hits one wheel at a time:
+///     hits[k] after wheel i = hits[k] * pi(x) + hits[k-1] * pi(banana)
 #[cfg(feature = "generate")]
-fn combinari(n: u8, k: u8) -> u64 {
-    factorial(n) / (factorial(k) * factorial(n - k))
+fn get_prob_for_index_and_density(reels: &[&BTreeMap<Fruit, u16>], fruit: Fruit, count: u8) -> f64 {
+    let total = reels.len();
+    assert!(count > 0);
+    assert!(count as usize <= total);
+    assert!(total > 1);
+
+    let mut hits = vec![0.0; total + 1];
+    hits[0] = 1.0;
+    for (i, reel) in reels.iter().enumerate() {
+        let p_fruit = *reel.get(&fruit).unwrap_or(&0) as f64 / u16::MAX as f64;
+        let p_x = 1.0 - p_fruit;
+        for k in (0..=i + 1).rev() {
+            let from_hit = if k > 0 { hits[k - 1] * p_fruit } else { 0.0 };
+            hits[k] = hits[k] * p_x + from_hit;
+        }
+    }
+    hits[count as usize]
 }
 
 impl RuleSet {
+    /// the reward of the free spins won on a paid spin counts towards that spin
     #[cfg(feature = "generate")]
     pub fn play_monte_carlo(&self, count: u32) -> f64 {
+        let avg_reward = self.play_monte_carlo_from(&mut rand::thread_rng(), count);
+        println!("avg_reward={avg_reward}  N={count}");
+        avg_reward
+    }
+    /// average win of `count` paid spins, free spins included, with every seed taken from `r`
+    #[cfg(feature = "generate")]
+    pub fn play_monte_carlo_from(&self, r: &mut impl rand::Rng, count: u32) -> f64 {
         let mut reward_total: f64 = 0.;
         for _i in 0..count {
-            reward_total += self.play_random().1 as f64;
+            reward_total += self.play_paid_spin_from(r).0 as f64;
         }
-        let avg_reward = reward_total / count as f64;
-        println!("avg_reward={avg_reward}  N={count}");
-        avg_reward
+        reward_total / REWARD_ONE as f64 / count as f64
     }
+    /// one paid spin and the free spins it brings: (total win in basis points, free spins played)
     #[cfg(feature = "generate")]
-    pub fn play_random(&self) -> (Vec<Fruit>, u16) {
-        use rand::Rng;
-
-        let r: [u16; 3] = rand::thread_rng().gen();
-        self.play_random_from_seed(r)
-    }
-    pub fn play_random_from_seed(&self, random_seed: [u16; 3]) -> (Vec<Fruit>, u16) {
-        let mut result = vec![];
-        let mut fruit_hits = BTreeMap::new();
-        for i in 0..3 {
-            let x = get_random_index_per_density(random_seed[i], &self.prob);
-            result.push(x);
-            let old_val = fruit_hits.get(&x);
-            let new_val = if let Some(old_val) = old_val {
-                old_val + 1
-            } else {
-                1
-            };
-            fruit_hits.insert(x, new_val);
-        }
-
-        // println!("hash = {fruit_hits:?}");
-        let mut reward: u32 = 0;
-        for (fruit, count) in fruit_hits.iter() {
-            let rrr = *self.rewards.get(&(*fruit, *count)).unwrap_or(&0);
-            reward += rrr as u32;
+    pub(crate) fn play_paid_spin_from(&self, r: &mut impl rand::Rng) -> (u64, u32) {
+        let spin = self.play_random_from_seed(&self.random_seed(r));
+        let mut reward_total = spin.reward as u64;
+        let mut free_spins = spin.free_spins as u32;
+        let mut played = 0;
+        while free_spins > 0 {
+            free_spins -= 1;
+            played += 1;
+            let spin = self.play_free_spin_from_seed(&self.random_seed(r));
+            reward_total += spin.reward as u64;
+            free_spins += spin.free_spins as u32;
+        }
+        (reward_total, played)
+    }
+    #[cfg(feature = "generate")]
+    fn random_seed(&self, r: &mut impl rand::Rng) -> Vec<u16> {
+        (0..self.wheel_count).map(|_| r.gen()).collect()
+    }
+    #[cfg(feature = "generate")]
+    pub fn play_random(&self) -> SpinOutcome {
+        self.play_random_from_seed(&self.random_seed(&mut rand::thread_rng()))
+    }
+    /// a spin paid by earlier free spins: same fruits as `play_random_from_seed`,
+    /// the win is multiplied by the scatter `win_multiplier`.
+    pub fn play_free_spin_from_seed(&self, random_seed: &[u16]) -> SpinOutcome {
+        let multiplier = self.scatter.map(|s| s.win_multiplier).unwrap_or(1);
+        self.play_from_seed(random_seed, multiplier)
+    }
+    /// One u16 per wheel is needed, `random_seed.len() == wheel_count`.
+    pub fn play_random_from_seed(&self, random_seed: &[u16]) -> SpinOutcome {
+        self.play_from_seed(random_seed, 1)
+    }
+    fn play_from_seed(&self, random_seed: &[u16], multiplier: u8) -> SpinOutcome {
+        assert_eq!(random_seed.len(), self.wheel_count as usize);
+        let mut symbols = vec![];
+        for (wheel, seed) in random_seed.iter().enumerate() {
+            let x = get_random_index_per_density(*seed, self.reel_prob(wheel));
+            symbols.push(x);
         }
 
-        result.sort_by_key(move |x| -(fruit_hits[x] as i32));
+        let matched = self.matched_rewards(&symbols);
+        let uncapped = uncapped_reward(&matched) * multiplier as u64;
+        let reward = uncapped.min(self.max_win as u64) as u32;
+        let mut caps = vec![];
+        if uncapped > reward as u64 {
+            caps.push(AppliedCap::MaxWin {
+                uncapped,
+                cap: self.max_win,
+            });
+        }
 
-        (result, reward.clamp(0, 55666) as u16)
+        SpinOutcome {
+            free_spins: self.free_spins_won(&symbols),
+            jackpot: self.jackpot_won(&symbols),
+            symbols,
+            stops: random_seed.to_vec(),
+            matched: matched
+                .into_iter()
+                .map(|((fruit, count), reward)| MatchedRule {
+                    fruit,
+                    count,
+                    reward,
+                })
+                .collect(),
+            multiplier,
+            reward,
+            caps,
+        }
+    }
+    pub(crate) fn jackpot_won(&self, fruits: &[Fruit]) -> bool {
+        let Some(jackpot) = self.jackpot else {
+            return false;
+        };
+        fruits.iter().filter(|f| **f == jackpot.fruit).count() >= jackpot.count as usize
+    }
+    pub(crate) fn free_spins_won(&self, fruits: &[Fruit]) -> u8 {
+        let Some(scatter) = self.scatter else {
+            return 0;
+        };
+        let hits = fruits.iter().filter(|f| **f == scatter.fruit).count();
+        if hits >= scatter.min_count as usize {
+            scatter.free_spins
+        } else {
+            0
+        }
     }
+    /// the `(fruit, count)` rules paid by the fruits on the wheels, in any order.
+    /// all the wilds on the wheels either count as themselves or all join
+    /// the same other fruit, whichever pays the most.
+    pub(crate) fn matched_rewards(&self, fruits: &[Fruit]) -> Vec<((Fruit, u8), u32)> {
+        let fruit_hits = count_fruit_hits(fruits);
+        let mut best = self.rewards_for_hits(&fruit_hits);
+        let wild_count: u8 = fruit_hits
+            .iter()
+            .filter(|(f, _)| self.wilds.contains(f))
+            .map(|(_, c)| *c)
+            .sum();
+        if wild_count == 0 {
+            return best;
+        }
+
+        let mut best_total = uncapped_reward(&best).min(self.max_win as u64);
+        for fruit in Fruit::all().iter() {
+            if self.wilds.contains(fruit) {
+                continue;
+            }
+            let mut hits: BTreeMap<Fruit, u8> = fruit_hits
+                .iter()
+                .filter(|(f, _)| !self.wilds.contains(f))
+                .map(|(f, c)| (*f, *c))
+                .collect();
+            *hits.entry(*fruit).or_insert(0) += wild_count;
+            let matched = self.rewards_for_hits(&hits);
+            let total = uncapped_reward(&matched).min(self.max_win as u64);
+            if total > best_total {
+                best = matched;
+                best_total = total;
+            }
+        }
+        best
+    }
+    fn rewards_for_hits(&self, fruit_hits: &BTreeMap<Fruit, u8>) -> Vec<((Fruit, u8), u32)> {
+        let mut matched = vec![];
+        for (fruit, count) in fruit_hits.iter() {
+            if let Some(reward) = self.rewards.get(&(*fruit, *count)) {
+                matched.push(((*fruit, *count), *reward));
+            }
+        }
+        matched
+    }
+    /// treats every combo as independent and does not know about wilds,
+    /// see `exact_return` for the real value
     #[cfg(feature = "generate")]
     pub fn projected_return(&self) -> f64 {
         let mut z = 0.0;
 
         for ((fruit, count), reward) in self.rewards.iter() {
-            let prob = get_prob_for_index_and_density(&self.prob, *fruit, *count, self.wheel_count);
-            let ev = prob * *reward as f64;
+            let prob = get_prob_for_index_and_density(&self.reels(), *fruit, *count);
+            let ev = prob * reward_multiple((*reward).min(self.max_win));
             z += ev;
 
             // println!("{fruit} x {count} ==> r={reward}  p={prob}  ev={ev}");
         }
         z
     }
+    /// Upper bound of the return to player, cheap enough for the program to check a
+    /// rule set it is sent; `exact_return` walks every outcome and is not.
+    /// Adds up every combo on its own, so it is exact without wilds as long as no
+    /// spin pays more than one combo or goes over `max_win`. A wild combo counts
+    /// once with and once without the wilds joining in.
+    pub fn return_bound(&self) -> f64 {
+        let multiplier = self.scatter.map(|s| s.win_multiplier).unwrap_or(1) as u64;
+        let capped = |reward: u64| reward_multiple(reward.min(self.max_win as u64) as u32);
+        let mut base = 0.0;
+        let mut free = 0.0;
+        for ((fruit, count), reward) in self.rewards.iter() {
+            let mut p = self.hit_count_chances(|f| f == *fruit)[*count as usize];
+            if !self.wilds.is_empty() && !self.wilds.contains(fruit) {
+                p += self.hit_count_chances(|f| f == *fruit || self.wilds.contains(&f))
+                    [*count as usize];
+            }
+            base += p * capped(*reward as u64);
+            free += p * capped(*reward as u64 * multiplier);
+        }
+        let trigger = self.free_spins_per_trigger();
+        if trigger >= 1.0 {
+            return f64::INFINITY;
+        }
+        let free_spins_per_spin = trigger / (1.0 - trigger);
+        let jackpot = self
+            .jackpot
+            .map(|j| j.contribution_bps as f64 / 10000.0)
+            .unwrap_or(0.0);
+        base + free_spins_per_spin * free + jackpot
+    }
+    /// free spins won on average by one spin, paid or free, see `par_sheet_over`;
+    /// from 1 up free spins never end
+    pub fn free_spins_per_trigger(&self) -> f64 {
+        match self.scatter {
+            Some(s) => {
+                let hits = self.hit_count_chances(|f| f == s.fruit);
+                hits[s.min_count as usize..].iter().sum::<f64>() * s.free_spins as f64
+            }
+            None => 0.0,
+        }
+    }
+    /// `chances[k]` = chance that exactly k wheels stop on a fruit `hit` picks
+    fn hit_count_chances(&self, hit: impl Fn(Fruit) -> bool) -> Vec<f64> {
+        let seed_count = u16::MAX as f64 + 1.0;
+        let mut chances = vec![0.0; self.wheel_count as usize + 1];
+        chances[0] = 1.0;
+        for (i, reel) in self.reels().iter().enumerate() {
+            // the first fruit gets one extra seed, see `get_random_index_per_density`
+            let mut seeds = 0.0;
+            let mut first = true;
+            for (fruit, p) in reel.iter().filter(|(_, p)| **p > 0) {
+                let extra = if first { 1.0 } else { 0.0 };
+                first = false;
+                if hit(*fruit) {
+                    seeds += *p as f64 + extra;
+                }
+            }
+            let p_hit = seeds / seed_count;
+            for k in (0..=i + 1).rev() {
+                let from_hit = if k > 0 { chances[k - 1] * p_hit } else { 0.0 };
+                chances[k] = chances[k] * (1.0 - p_hit) + from_hit;
+            }
+        }
+        chances
+    }
     #[cfg(feature = "generate")]
-    pub fn random_rule_set(desired_pay: f64) -> Self {
-        use rand::Rng;
-
+    pub fn random_rule_set(desired_pay: f64, wheel_count: u8) -> Self {
+        Self::random_rule_set_with_wilds(desired_pay, wheel_count, &[])
+    }
+    #[cfg(feature = "generate")]
+    pub fn random_rule_set_with_wilds(desired_pay: f64, wheel_count: u8, wilds: &[Fruit]) -> Self {
+        let shape = RewardShape::default();
+        Self::random_rule_set_from(
+            &mut rand::thread_rng(),
+            desired_pay,
+            wheel_count,
+            wilds,
+            &shape,
+        )
+    }
+    /// Same as `random_rule_set_with_wilds`, but every random pick comes from `r`,
+    /// so a seeded `r` always gives back the same rule set.
+    #[cfg(feature = "generate")]
+    pub fn random_rule_set_from(
+        r: &mut impl rand::Rng,
+        desired_pay: f64,
+        wheel_count: u8,
+        wilds: &[Fruit],
+        shape: &RewardShape,
+    ) -> Self {
         assert!(desired_pay >= 0.5);
         assert!(desired_pay <= 2.0);
-
-        let wheel_count = 3;
+        assert!(wheel_count >= 2);
+        assert!(wilds.len() < Fruit::all().len());
+        assert!(shape.max_win > 3);
+        let wilds = BTreeSet::from_iter(wilds.iter().cloned());
+        let max_win = shape.max_win as u32 * REWARD_ONE;
 
         let fruits = Fruit::all();
         let fruits_len_f64 = fruits.len() as f64;
-        let prob = make_random_prob_space(fruits.len());
+        let prob = make_random_prob_space_from(r, fruits.len(), shape.prob_var);
         let prob: BTreeMap<Fruit, u16> =
             BTreeMap::from_iter(fruits.iter().cloned().zip(prob.iter().cloned()));
 
+        let reels = vec![&prob; wheel_count as usize];
         let mut rewards = BTreeMap::new();
         for fruit in fruits {
             // let p_fruit = prob[&fruit] as f64 / std::u16::MAX as f64;
             for score in 1..=wheel_count {
-                let prob = get_prob_for_index_and_density(&prob, *fruit, score, wheel_count);
+                let prob = get_prob_for_index_and_density(&reels, *fruit, score);
                 assert!(prob > 0.0);
                 assert!(prob < 1.0);
-                let max_reward = (desired_pay / prob / fruits_len_f64).clamp(0.0, 55666.0).powf(0.8);
-                let max_reward_i = max_reward as u64;
-                let r_u16 = max_reward_i.clamp(0, 55666) as u16;
-                // println!("{fruit}x{score}   =>>>   reward_f: {max_reward}, reward_i: {max_reward_i}, reward_u16 = {r_u16}");
-                rewards.insert((fruit.clone(), score), r_u16);
+                let max_reward = (desired_pay / prob / fruits_len_f64)
+                    .clamp(0.0, shape.max_win as f64)
+                    .powf(shape.reward_exponent);
+                // combos that would not even give the bet back are left out
+                if max_reward < 1.0 {
+                    continue;
+                }
+                let reward = ((max_reward * REWARD_ONE as f64).round() as u32).min(max_win);
+                // println!("{fruit}x{score}   =>>>   reward_f: {max_reward}, reward = {reward}");
+                rewards.insert((*fruit, score), reward);
             }
         }
 
-        // hardcode cherry
-        rewards.insert((Fruit::cherry, 1), 1);
-
+        // wilds pay more than the single combos add up to, so use the exact value
+        let mut rule_set = RuleSet {
+            prob: prob.clone(),
+            rewards: BTreeMap::new(),
+            wheel_count,
+            reel_prob: vec![],
+            wilds: wilds.clone(),
+            scatter: shape.scatter,
+            jackpot: shape.jackpot,
+            max_win,
+        };
+        let dist = crate::par_sheet::outcome_distribution(&rule_set);
         for _ in 0..10 {
-            let projected = RuleSet {
-                prob: prob.clone(),
-                rewards: rewards.clone(),
-                wheel_count,
-            }
-            .projected_return();
-            let coef = desired_pay / projected;
+            rule_set.rewards = rewards.clone();
+            // the jackpot contribution does not scale with the rewards
+            let sheet = rule_set.par_sheet_over(&dist);
+            let coef = (desired_pay - sheet.jackpot_rtp) / (sheet.rtp - sheet.jackpot_rtp);
             for (_k, _v) in rewards.iter_mut() {
-                if *_v > 3 {
-                    *_v = ((*_v as f64) * coef * rand::thread_rng().gen_range(0.9999..1.0))
-                        .floor()
-                        .clamp(0.0, 55666.0) as u16;
-                }
+                *_v =
+                    (((*_v as f64) * coef * r.gen_range(0.9999..1.0)).round() as u32).min(max_win);
             }
-            println!("projected: {projected}");
         }
         // filter rewards with 0 score
         let rewards = BTreeMap::from_iter(rewards.into_iter().filter(|k| k.1 > 0));
@@ -215,10 +549,16 @@ impl RuleSet {
             prob,
             rewards,
             wheel_count,
+            reel_prob: vec![],
+            wilds,
+            scatter: shape.scatter,
+            jackpot: shape.jackpot,
+            max_win,
         }
     }
 
-    pub fn default_internal_deserialize() -> Self {
+    /// the bundled `RuleSetId::DEFAULT` table, `rules publish` writes the file
+    pub fn default_internal_deserialize() -> Result<Self, RuleSetError> {
         let b = include_bytes!("default_pacanea_rule_set.bin");
         Self::deserialize(b)
     }
@@ -226,23 +566,146 @@ impl RuleSet {
     pub fn prob(&self) -> BTreeMap<Fruit, u16> {
         self.prob.clone()
     }
-    pub fn rewards(&self) -> BTreeMap<(Fruit, u8), u16> {
+    pub fn rewards(&self) -> BTreeMap<(Fruit, u8), u32> {
         self.rewards.clone()
     }
-
-    pub fn serialize(&self) -> Vec<u8> {
-        // borsh::to_vec(&self).unwrap()
-        bincode::serialize(self).unwrap()
+    pub fn wheel_count(&self) -> u8 {
+        self.wheel_count
+    }
+    /// probability table used by one wheel
+    pub fn reel_prob(&self, wheel: usize) -> &BTreeMap<Fruit, u16> {
+        self.reel_prob.get(wheel).unwrap_or(&self.prob)
+    }
+    /// probability tables for all the wheels, in wheel order
+    pub fn reels(&self) -> Vec<&BTreeMap<Fruit, u16>> {
+        (0..self.wheel_count as usize)
+            .map(|wheel| self.reel_prob(wheel))
+            .collect()
+    }
+    pub fn wilds(&self) -> BTreeSet<Fruit> {
+        self.wilds.clone()
+    }
+    pub fn set_wilds(&mut self, wilds: &[Fruit]) {
+        assert!(wilds.len() < Fruit::all().len());
+        self.wilds = BTreeSet::from_iter(wilds.iter().cloned());
+    }
+    pub fn scatter(&self) -> Option<ScatterRule> {
+        self.scatter
+    }
+    pub fn set_scatter(&mut self, scatter: Option<ScatterRule>) {
+        if let Some(scatter) = scatter {
+            assert!(scatter.min_count > 0);
+            assert!(scatter.min_count <= self.wheel_count);
+            assert!(scatter.win_multiplier > 0);
+            assert!(!self.wilds.contains(&scatter.fruit));
+        }
+        self.scatter = scatter;
+    }
+    /// in basis points of the bet
+    pub fn max_win(&self) -> u32 {
+        self.max_win
+    }
+    pub fn set_max_win(&mut self, max_win: u32) {
+        assert!(max_win > 0);
+        assert!(self.rewards.values().all(|r| *r <= max_win));
+        self.max_win = max_win;
+    }
+    pub fn jackpot(&self) -> Option<JackpotRule> {
+        self.jackpot
+    }
+    pub fn set_jackpot(&mut self, jackpot: Option<JackpotRule>) {
+        if let Some(jackpot) = jackpot {
+            assert!(jackpot.count > 0);
+            assert!(jackpot.count <= self.wheel_count);
+            assert!(jackpot.contribution_bps < 10000);
+        }
+        self.jackpot = jackpot;
+    }
+    /// give each wheel its own probability table, e.g. make the last wheel scarce in sevens.
+    /// every table must sum to u16::MAX, like `prob`.
+    pub fn set_reel_prob(&mut self, reel_prob: Vec<BTreeMap<Fruit, u16>>) {
+        assert_eq!(reel_prob.len(), self.wheel_count as usize);
+        for reel in reel_prob.iter() {
+            assert!(reel.len() > 2);
+            let sum: u32 = reel.values().map(|v| *v as u32).sum();
+            assert_eq!(sum, u16::MAX as u32);
+        }
+        self.reel_prob = reel_prob;
     }
 
-    pub fn deserialize(v: &[u8]) -> Self {
-        // borsh::from_slice(v).unwrap()
-        bincode::deserialize(v).unwrap()
+    /// Check everything `play_random_from_seed` and the par sheet rely on.
+    pub fn validate(&self) -> Result<(), RuleSetError> {
+        if self.wheel_count == 0 {
+            return Err(RuleSetError::WheelCount(self.wheel_count));
+        }
+        if !self.reel_prob.is_empty() && self.reel_prob.len() != self.wheel_count as usize {
+            return Err(RuleSetError::ReelCount {
+                wheel_count: self.wheel_count,
+                reels: self.reel_prob.len(),
+            });
+        }
+        if self.max_win == 0 {
+            return Err(RuleSetError::MaxWin(self.max_win));
+        }
+        if let Some(fruit) = Fruit::all().iter().find(|f| !self.prob.contains_key(f)) {
+            return Err(RuleSetError::MissingFruit(*fruit));
+        }
+        let tables = std::iter::once((None, &self.prob))
+            .chain(self.reel_prob.iter().enumerate().map(|(i, p)| (Some(i), p)));
+        for (reel, prob) in tables {
+            let sum: u32 = prob.values().map(|v| *v as u32).sum();
+            if sum != u16::MAX as u32 {
+                return Err(RuleSetError::ProbSum { reel, sum });
+            }
+            if prob.values().filter(|v| **v > 0).count() < 3 {
+                return Err(RuleSetError::TooFewFruits { reel });
+            }
+        }
+        for ((fruit, count), reward) in self.rewards.iter() {
+            if *count == 0 || *count > self.wheel_count {
+                return Err(RuleSetError::RewardCount {
+                    fruit: *fruit,
+                    count: *count,
+                });
+            }
+            if *reward > self.max_win {
+                return Err(RuleSetError::RewardTooBig {
+                    fruit: *fruit,
+                    count: *count,
+                    reward: *reward,
+                });
+            }
+        }
+        if self.wilds.len() >= Fruit::all().len() {
+            return Err(RuleSetError::Wilds);
+        }
+        if let Some(scatter) = self.scatter {
+            if scatter.min_count == 0
+                || scatter.min_count > self.wheel_count
+                || scatter.win_multiplier == 0
+                || self.wilds.contains(&scatter.fruit)
+            {
+                return Err(RuleSetError::Scatter);
+            }
+            if self.free_spins_per_trigger() >= 1.0 {
+                return Err(RuleSetError::EndlessFreeSpins);
+            }
+        }
+        if let Some(jackpot) = self.jackpot {
+            if jackpot.count == 0
+                || jackpot.count > self.wheel_count
+                || jackpot.contribution_bps >= 10000
+            {
+                return Err(RuleSetError::Jackpot);
+            }
+        }
+        Ok(())
     }
 
     #[cfg(feature = "generate")]
     pub fn rust_constructor(&self, name: &str) -> String {
         let wheel_count = self.wheel_count;
+        let max_win = self.max_win;
         let prob_rows = self
             .prob
             .iter()
@@ -250,6 +713,59 @@ impl RuleSet {
             .collect::<Vec<_>>()
             .join(",\n");
 
+        let reel_rows = self
+            .reel_prob
+            .iter()
+            .map(|reel| {
+                let rows = reel
+                    .iter()
+                    .map(|(k, v)| format!("(Fruit::{k:?}, {v})"))
+                    .collect::<Vec<_>>()
+                    .join(",\n");
+                format!("BTreeMap::<Fruit, u16>::from([{rows}])")
+            })
+            .collect::<Vec<_>>()
+            .join(",\n");
+
+        let wild_rows = self
+            .wilds
+            .iter()
+            .map(|k| format!("Fruit::{k:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let scatter_row = match self.scatter {
+            Some(ScatterRule {
+                fruit,
+                min_count,
+                free_spins,
+                win_multiplier,
+            }) => format!(
+                "Some(crate::rule_set::ScatterRule {{
+                    fruit: Fruit::{fruit:?},
+                    min_count: {min_count},
+                    free_spins: {free_spins},
+                    win_multiplier: {win_multiplier},
+                }})"
+            ),
+            None => "None".to_string(),
+        };
+
+        let jackpot_row = match self.jackpot {
+            Some(JackpotRule {
+                fruit,
+                count,
+                contribution_bps,
+            }) => format!(
+                "Some(crate::rule_set::JackpotRule {{
+                    fruit: Fruit::{fruit:?},
+                    count: {count},
+                    contribution_bps: {contribution_bps},
+                }})"
+            ),
+            None => "None".to_string(),
+        };
+
         let reward_rows = self
             .rewards
             .iter()
@@ -257,21 +773,270 @@ impl RuleSet {
             .collect::<Vec<_>>()
             .join(",\n");
 
+        // the same rule set as `CompiledRuleSet`, for the on-chain program
+        let const_name = name.to_uppercase();
+        let cdf_rows = self
+            .cdf_table()
+            .iter()
+            .map(|row| format!("{row:?}"))
+            .collect::<Vec<_>>()
+            .join(",\n");
+        let reward_table = format!("{:?}", self.reward_table());
+        let wild_flags = format!("{:?}", self.compiled(&[], &[]).wilds);
+        let content_hash = self.content_hash();
+
         format!(
-            "
-        use crate::Fruit;
+            "use crate::Fruit;
         use crate::rule_set::RuleSet;
-        use std::collections::BTreeMap;
+        use std::collections::{{BTreeMap, BTreeSet}};
         impl RuleSet {{
             pub fn {name}() -> Self {{
                 Self {{
                     prob: BTreeMap::<Fruit, u16>::from([{prob_rows}]),
-                    rewards: BTreeMap::<(Fruit, u8), u16>::from([{reward_rows}]),
+                    rewards: BTreeMap::<(Fruit, u8), u32>::from([{reward_rows}]),
                     wheel_count: {wheel_count},
+                    reel_prob: vec![{reel_rows}],
+                    wilds: BTreeSet::<Fruit>::from([{wild_rows}]),
+                    scatter: {scatter_row},
+                    jackpot: {jackpot_row},
+                    max_win: {max_win},
                 }}
             }}
         }}
+        /// `RuleSet::content_hash` of `{name}()`
+        pub const {const_name}_HASH: u64 = {content_hash:#018x};
+        pub const {const_name}: crate::compiled::CompiledRuleSet<'static> = crate::compiled::CompiledRuleSet {{
+            wheel_count: {wheel_count},
+            cdf: &[{cdf_rows}],
+            rewards: &{reward_table},
+            wilds: {wild_flags},
+            scatter: {scatter_row},
+            jackpot: {jackpot_row},
+            max_win: {max_win},
+        }};
         "
         )
     }
 }
+
+#[cfg(all(test, feature = "generate"))]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_return_bound() {
+        let plain = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(1),
+            0.9,
+            3,
+            &[],
+            &RewardShape::default(),
+        );
+        let exact = plain.exact_return();
+        assert!(plain.return_bound() >= exact - 1e-9);
+        assert!(
+            plain.return_bound() < exact + 0.01,
+            "{} {}",
+            plain.return_bound(),
+            exact
+        );
+
+        let mut r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(2),
+            0.9,
+            4,
+            &[Fruit::bell],
+            &RewardShape::default(),
+        );
+        r.set_scatter(Some(ScatterRule {
+            fruit: Fruit::bananas,
+            min_count: 3,
+            free_spins: 5,
+            win_multiplier: 2,
+        }));
+        r.set_jackpot(Some(JackpotRule {
+            fruit: Fruit::watermelon,
+            count: 4,
+            contribution_bps: 100,
+        }));
+        assert!(r.return_bound() >= r.exact_return() - 1e-9);
+    }
+
+    #[test]
+    fn test_five_wheels_roundtrip() {
+        let r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(3),
+            0.9,
+            5,
+            &[],
+            &RewardShape::default(),
+        );
+        assert_eq!(r.wheel_count(), 5);
+
+        let r2 = RuleSet::deserialize(&r.serialize()).unwrap();
+        assert_eq!(r2.wheel_count(), 5);
+        assert!(r2.rewards().keys().any(|(_, count)| *count == 5));
+
+        let seed = [0, 1000, 20000, 40000, 65535];
+        let spin = r2.play_random_from_seed(&seed);
+        assert_eq!(spin.symbols.len(), 5);
+        assert_eq!(
+            r.play_random_from_seed(&seed),
+            r2.play_random_from_seed(&seed)
+        );
+    }
+
+    #[test]
+    fn test_reel_prob_without_seven() {
+        let mut r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(4),
+            0.9,
+            3,
+            &[],
+            &RewardShape::default(),
+        );
+        let same_return = r.projected_return();
+
+        // move all the sevens on the last wheel to bananas
+        let mut last = r.prob();
+        let sevens = last.remove(&Fruit::seven).unwrap();
+        *last.get_mut(&Fruit::bananas).unwrap() += sevens;
+        r.set_reel_prob(vec![r.prob(), r.prob(), last]);
+
+        for seed in (0..=u16::MAX).step_by(7) {
+            let spin = r.play_random_from_seed(&[seed, seed, seed]);
+            assert!(spin.symbols.iter().filter(|f| **f == Fruit::seven).count() < 3);
+        }
+        let reels = r.reels();
+        assert_eq!(get_prob_for_index_and_density(&reels, Fruit::seven, 3), 0.0);
+        assert!(r.projected_return() != same_return);
+
+        let r2 = RuleSet::deserialize(&r.serialize()).unwrap();
+        assert_eq!(r2.reel_prob(2), r.reel_prob(2));
+    }
+
+    #[test]
+    fn test_wilds_pick_best_combo() {
+        let mut r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(5),
+            0.9,
+            3,
+            &[],
+            &RewardShape::default(),
+        );
+        let no_wild_return = r.exact_return();
+        let seven_3 = r.rewards()[&(Fruit::seven, 3)];
+        r.set_wilds(&[Fruit::dollars]);
+
+        let matched = r.matched_rewards(&[Fruit::dollars, Fruit::seven, Fruit::seven]);
+        assert_eq!(matched, vec![((Fruit::seven, 3), seven_3)]);
+        let matched = r.matched_rewards(&[Fruit::dollars, Fruit::dollars, Fruit::dollars]);
+        assert_eq!(matched, vec![((Fruit::seven, 3), seven_3)]);
+        assert!(r.exact_return() > no_wild_return);
+
+        let r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(6),
+            0.9,
+            3,
+            &[Fruit::dollars],
+            &RewardShape::default(),
+        );
+        assert!((r.exact_return() - 0.9).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(7),
+            0.9,
+            3,
+            &[],
+            &RewardShape::default(),
+        );
+        assert_eq!(r.validate(), Ok(()));
+
+        r.rewards.insert((Fruit::seven, 4), 10 * REWARD_ONE);
+        assert_eq!(
+            r.validate(),
+            Err(RuleSetError::RewardCount {
+                fruit: Fruit::seven,
+                count: 4
+            })
+        );
+        r.rewards.remove(&(Fruit::seven, 4));
+
+        r.max_win = 10 * REWARD_ONE;
+        assert!(matches!(
+            r.validate(),
+            Err(RuleSetError::RewardTooBig { .. })
+        ));
+        r.max_win = 0;
+        assert_eq!(r.validate(), Err(RuleSetError::MaxWin(0)));
+        r.max_win = 55666 * REWARD_ONE;
+
+        let common = *r.prob.iter().max_by_key(|(_, p)| **p).unwrap().0;
+        r.scatter = Some(ScatterRule {
+            fruit: common,
+            min_count: 1,
+            free_spins: 255,
+            win_multiplier: 1,
+        });
+        assert_eq!(r.validate(), Err(RuleSetError::EndlessFreeSpins));
+        r.scatter = None;
+
+        *r.prob.get_mut(&Fruit::seven).unwrap() -= 1;
+        assert_eq!(
+            r.validate(),
+            Err(RuleSetError::ProbSum {
+                reel: None,
+                sum: u16::MAX as u32 - 1
+            })
+        );
+        assert!(RuleSet::deserialize(&r.serialize()).is_err());
+    }
+
+    #[test]
+    fn test_spin_outcome() {
+        let mut r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(8),
+            0.9,
+            3,
+            &[],
+            &RewardShape::default(),
+        );
+        r.rewards.insert((Fruit::bananas, 2), 40000 * REWARD_ONE);
+        r.rewards.insert((Fruit::seven, 1), 20000 * REWARD_ONE);
+
+        let spin = r.play_random_from_seed(&[0, u16::MAX, 0]);
+        assert_eq!(
+            spin.symbols,
+            vec![Fruit::bananas, Fruit::seven, Fruit::bananas]
+        );
+        assert_eq!(spin.stops, vec![0, u16::MAX, 0]);
+        assert_eq!(spin.multiplier, 1);
+        assert!(spin.matched.contains(&MatchedRule {
+            fruit: Fruit::bananas,
+            count: 2,
+            reward: 40000 * REWARD_ONE
+        }));
+        let uncapped: u64 = spin.matched.iter().map(|m| m.reward as u64).sum();
+        assert_eq!(spin.reward, r.max_win());
+        assert_eq!(
+            spin.caps,
+            vec![AppliedCap::MaxWin {
+                uncapped,
+                cap: r.max_win()
+            }]
+        );
+
+        let spin = r.play_random_from_seed(&[0, 0, 0]);
+        assert_eq!(spin.caps, vec![]);
+        assert_eq!(
+            spin.reward,
+            spin.matched.iter().map(|m| m.reward).sum::<u32>()
+        );
+    }
+}
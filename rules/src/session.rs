@@ -0,0 +1,344 @@
+//! Player sessions: a bankroll, a way to size the bets and a number of paid spins,
+//! played many times to see how a player fares, not only what the house keeps.
+//! Bets are `1 << exponent` lamports and wins are paid like the program pays them.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::rule_set::{RuleSet, REWARD_ONE};
+
+/// Sessions per unit of work, every session has its own ChaCha stream so the
+/// result only depends on the spec.
+const CHUNK_SESSIONS: u64 = 256;
+
+/// Percentiles of the final balance in `SessionReport::final_percentiles`.
+const PERCENTILES: [f64; 7] = [0.01, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum BetStrategy {
+    /// the same bet on every spin
+    Flat { bet_exp: u8 },
+    /// one more in the exponent (double the bet) after a loss, back to `base_exp` after
+    /// a win, never over `max_exp`. When the bankroll can't cover the bet it bets all
+    /// it can in whole exponents.
+    Martingale { base_exp: u8, max_exp: u8 },
+}
+
+impl BetStrategy {
+    fn base_exp(&self) -> u8 {
+        match self {
+            BetStrategy::Flat { bet_exp } => *bet_exp,
+            BetStrategy::Martingale { base_exp, .. } => *base_exp,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SessionSpec {
+    pub seed: u64,
+    pub sessions: u64,
+    /// lamports the player starts with
+    pub bankroll: u64,
+    pub strategy: BetStrategy,
+    /// paid spins the player wants to play, free spins come on top
+    pub max_spins: u32,
+    /// stop after losing this many lamports
+    pub stop_loss: Option<u64>,
+    /// stop after winning this many lamports
+    pub stop_win: Option<u64>,
+}
+
+impl Default for SessionSpec {
+    fn default() -> Self {
+        SessionSpec {
+            seed: 0,
+            sessions: 10_000,
+            bankroll: 1 << 30,
+            strategy: BetStrategy::Flat { bet_exp: 23 },
+            max_spins: 500,
+            stop_loss: None,
+            stop_win: None,
+        }
+    }
+}
+
+/// Why a session ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub enum SessionEnd {
+    /// not enough left for the smallest bet of the strategy
+    Ruin,
+    StopLoss,
+    StopWin,
+    /// played all `max_spins`
+    Length,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Session {
+    balance: u64,
+    spins: u32,
+    end: SessionEnd,
+}
+
+/// Result of `RuleSet::simulate_sessions`. The jackpot is left out, like in
+/// `RuleSet::simulate`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SessionReport {
+    pub spec: SessionSpec,
+    /// part of the sessions that ended broke
+    pub risk_of_ruin: f64,
+    /// how many sessions ended for each reason
+    pub ends: BTreeMap<SessionEnd, u64>,
+    /// paid spins per session
+    pub mean_spins: f64,
+    pub mean_final: f64,
+    /// (percentile, final balance in lamports)
+    pub final_percentiles: Vec<(f64, u64)>,
+    /// part of the sessions that ended with more than they started with
+    pub ahead: f64,
+}
+
+impl SessionSpec {
+    fn play(&self, rule_set: &RuleSet, rng: &mut ChaCha8Rng) -> Session {
+        let min_bet = 1u64 << self.strategy.base_exp();
+        let stop_low = self
+            .stop_loss
+            .map(|l| self.bankroll.saturating_sub(l))
+            .unwrap_or(0);
+        let stop_high = self.stop_win.map(|w| self.bankroll.saturating_add(w));
+        let mut balance = self.bankroll;
+        let mut exp = self.strategy.base_exp();
+        let mut spins = 0;
+        let end = loop {
+            if balance < min_bet {
+                break SessionEnd::Ruin;
+            }
+            if self.stop_loss.is_some() && balance <= stop_low {
+                break SessionEnd::StopLoss;
+            }
+            if stop_high.is_some_and(|high| balance >= high) {
+                break SessionEnd::StopWin;
+            }
+            if spins == self.max_spins {
+                break SessionEnd::Length;
+            }
+
+            while balance < 1 << exp {
+                exp -= 1;
+            }
+            let bet = 1u64 << exp;
+            let (reward, _) = rule_set.play_paid_spin_from(rng);
+            let win = (bet as u128 * reward as u128 / REWARD_ONE as u128) as u64;
+            balance = balance - bet + win;
+            spins += 1;
+
+            if let BetStrategy::Martingale { base_exp, max_exp } = self.strategy {
+                exp = if win >= bet {
+                    base_exp
+                } else {
+                    (exp + 1).min(max_exp)
+                };
+            }
+        };
+        Session {
+            balance,
+            spins,
+            end,
+        }
+    }
+}
+
+impl RuleSet {
+    /// Play `spec.sessions` sessions on every core.
+    pub fn simulate_sessions(&self, spec: &SessionSpec) -> SessionReport {
+        let (base_exp, max_exp) = match spec.strategy {
+            BetStrategy::Flat { bet_exp } => (bet_exp, bet_exp),
+            BetStrategy::Martingale { base_exp, max_exp } => (base_exp, max_exp),
+        };
+        assert!(spec.sessions > 0);
+        assert!(base_exp <= max_exp && max_exp < 63, "bad bet exponents");
+        assert!(
+            spec.bankroll >= 1 << base_exp,
+            "bankroll under the first bet"
+        );
+
+        let chunk_count = spec.sessions.div_ceil(CHUNK_SESSIONS) as usize;
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(chunk_count);
+        let next = AtomicUsize::new(0);
+        let done = Mutex::new(BTreeMap::new());
+        std::thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed) as u64;
+                    if index >= chunk_count as u64 {
+                        break;
+                    }
+                    let first = index * CHUNK_SESSIONS;
+                    let last = (first + CHUNK_SESSIONS).min(spec.sessions);
+                    let sessions: Vec<Session> = (first..last)
+                        .map(|i| {
+                            let mut rng = ChaCha8Rng::seed_from_u64(spec.seed);
+                            rng.set_stream(i);
+                            spec.play(self, &mut rng)
+                        })
+                        .collect();
+                    done.lock().unwrap().insert(index, sessions);
+                });
+            }
+        });
+        let sessions: Vec<Session> = done.into_inner().unwrap().into_values().flatten().collect();
+
+        let n = sessions.len() as f64;
+        let mut ends = BTreeMap::new();
+        for s in sessions.iter() {
+            *ends.entry(s.end).or_insert(0) += 1;
+        }
+        let mut finals: Vec<u64> = sessions.iter().map(|s| s.balance).collect();
+        finals.sort_unstable();
+        let final_percentiles = PERCENTILES
+            .iter()
+            .map(|p| {
+                let i = ((p * n).ceil() as usize).clamp(1, finals.len()) - 1;
+                (*p, finals[i])
+            })
+            .collect();
+        SessionReport {
+            spec: spec.clone(),
+            risk_of_ruin: ends.get(&SessionEnd::Ruin).copied().unwrap_or(0) as f64 / n,
+            ends,
+            mean_spins: sessions.iter().map(|s| s.spins as f64).sum::<f64>() / n,
+            mean_final: finals.iter().map(|b| *b as f64).sum::<f64>() / n,
+            final_percentiles,
+            ahead: finals.iter().filter(|b| **b > spec.bankroll).count() as f64 / n,
+        }
+    }
+}
+
+impl std::fmt::Display for SessionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let spec = &self.spec;
+        let sol = |lamports: f64| lamports / 1_000_000_000.0;
+        writeln!(
+            f,
+            "seed: {}  sessions: {}  bankroll: {} SOL  strategy: {:?}",
+            spec.seed,
+            spec.sessions,
+            sol(spec.bankroll as f64),
+            spec.strategy
+        )?;
+        writeln!(
+            f,
+            "max spins: {}  stop loss: {:?}  stop win: {:?}",
+            spec.max_spins, spec.stop_loss, spec.stop_win
+        )?;
+        writeln!(
+            f,
+            "risk of ruin: {:.6}  ahead: {:.6}  mean spins: {:.1}  mean final: {:.6} SOL",
+            self.risk_of_ruin,
+            self.ahead,
+            self.mean_spins,
+            sol(self.mean_final)
+        )?;
+        writeln!(f, "| end | sessions | share |\n|---|---|---|")?;
+        for (end, count) in self.ends.iter() {
+            writeln!(
+                f,
+                "| {end:?} | {count} | {:.6} |",
+                *count as f64 / spec.sessions as f64
+            )?;
+        }
+        writeln!(f, "| percentile | final SOL | of bankroll |\n|---|---|---|")?;
+        for (p, balance) in self.final_percentiles.iter() {
+            writeln!(
+                f,
+                "| {} | {:.6} | {:.3} |",
+                p * 100.0,
+                sol(*balance as f64),
+                *balance as f64 / spec.bankroll as f64
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rule_set::RewardShape;
+
+    #[test]
+    fn test_sessions() {
+        let r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(10),
+            0.9,
+            3,
+            &[],
+            &RewardShape::default(),
+        );
+        let spec = SessionSpec {
+            seed: 3,
+            sessions: 2000,
+            bankroll: 100 << 20,
+            strategy: BetStrategy::Flat { bet_exp: 20 },
+            max_spins: 300,
+            stop_loss: None,
+            stop_win: None,
+        };
+        let report = r.simulate_sessions(&spec);
+        assert_eq!(report, r.simulate_sessions(&spec));
+        assert_eq!(report.ends.values().sum::<u64>(), spec.sessions);
+        assert!(report.mean_spins <= spec.max_spins as f64);
+        // 300 bets at 10% house edge lose 30 bets on average out of 100
+        let expected = (100.0 - 300.0 * 0.1) * (1 << 20) as f64;
+        assert!((report.mean_final - expected).abs() < 0.1 * expected);
+
+        let stops = SessionSpec {
+            stop_loss: Some(20 << 20),
+            stop_win: Some(20 << 20),
+            ..spec.clone()
+        };
+        let stopped = r.simulate_sessions(&stops);
+        assert_eq!(stopped.risk_of_ruin, 0.0);
+        assert!(stopped.mean_spins < report.mean_spins);
+        // the last spin can go a fraction of a bet past the stop
+        assert!(stopped.final_percentiles[0].1 >= 79 << 20);
+    }
+
+    #[test]
+    fn test_martingale_ruins_more() {
+        let r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(11),
+            0.9,
+            3,
+            &[],
+            &RewardShape::default(),
+        );
+        let flat = SessionSpec {
+            seed: 5,
+            sessions: 2000,
+            bankroll: 64 << 20,
+            strategy: BetStrategy::Flat { bet_exp: 20 },
+            max_spins: 200,
+            stop_loss: None,
+            stop_win: None,
+        };
+        let martingale = SessionSpec {
+            strategy: BetStrategy::Martingale {
+                base_exp: 20,
+                max_exp: 26,
+            },
+            ..flat.clone()
+        };
+        let flat = r.simulate_sessions(&flat);
+        let martingale = r.simulate_sessions(&martingale);
+        assert!(martingale.risk_of_ruin > flat.risk_of_ruin);
+        assert!(martingale.mean_spins < flat.mean_spins);
+    }
+}
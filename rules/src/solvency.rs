@@ -0,0 +1,393 @@
+//! The house bank against many players at once, with the bet and payout limits of
+//! `bank`. Every round all players read the bank balance, pick a bet the client
+//! would offer them, and then the program plays their spins one after the other.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::bank::{max_bet_exp, max_payout, min_bank_for_bet, EMPTY_ACCOUNT_RENT, RENT_PER_BYTE};
+use crate::compiled::CompiledRuleSet;
+use crate::rule_set::{RuleSet, REWARD_ONE};
+use crate::Fruit;
+
+/// What the client takes off the bank balance before it sizes the bets: the rent
+/// of a 1 byte account and the transaction fees without a priority fee.
+pub const CLIENT_BANK_MARGIN: u64 = EMPTY_ACCOUNT_RENT + RENT_PER_BYTE + 2 * 5000;
+
+/// `recommend_bank` stops once the smallest good bank is known to this part of it.
+const SEARCH_PRECISION: f64 = 0.01;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SolvencySpec {
+    pub seed: u64,
+    /// independent histories of the bank
+    pub runs: u64,
+    /// lamports in the bank at the start
+    pub bank: u64,
+    /// players spinning at the same time
+    pub players: u32,
+    /// paid spins of every player, free spins come on top
+    pub rounds: u32,
+    /// bets are `1 << exp` lamports, every player picks one in this range at random
+    /// and the client lowers it to what the bank allows
+    pub bet_exps: (u8, u8),
+    /// report how often the bank went below these, in lamports
+    pub thresholds: Vec<u64>,
+}
+
+impl Default for SolvencySpec {
+    fn default() -> Self {
+        SolvencySpec {
+            seed: 0,
+            runs: 1000,
+            bank: 100_000_000_000,
+            players: 20,
+            rounds: 500,
+            bet_exps: (20, 24),
+            thresholds: vec![],
+        }
+    }
+}
+
+/// What one history of the bank saw.
+#[derive(Debug, Default, Clone, Copy)]
+struct Run {
+    final_bank: u64,
+    lowest_bank: u64,
+    /// spins that the client would not offer or the program refused
+    refused: u64,
+    /// spins that won anything, paid and free
+    wins: u64,
+    capped: u64,
+    owed: u128,
+    short: u128,
+}
+
+/// Result of `RuleSet::simulate_solvency`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SolvencyReport {
+    pub spec: SolvencySpec,
+    /// (threshold, part of the runs in which the bank went below it)
+    pub below: Vec<(u64, f64)>,
+    /// smallest bank at which the client still offers the largest bet
+    pub full_service_bank: u64,
+    /// part of the runs in which a payout was capped or the largest bet was taken off
+    pub degraded: f64,
+    /// part of the runs with at least one capped payout
+    pub capped_runs: f64,
+    /// part of the winning spins the bank could not pay in full
+    pub capped_rate: f64,
+    /// part of all wins, in lamports, that was not paid
+    pub short_share: f64,
+    /// part of the bets the bank was too small to take
+    pub refused_rate: f64,
+    pub mean_final_bank: f64,
+    pub lowest_bank: u64,
+}
+
+/// Result of `RuleSet::recommend_bank`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct BankRecommendation {
+    pub max_risk: f64,
+    /// smallest bank found with `report.degraded <= max_risk`
+    pub bank: u64,
+    pub report: SolvencyReport,
+}
+
+impl SolvencySpec {
+    fn full_service_bank(&self) -> u64 {
+        min_bank_for_bet(self.bet_exps.1) + CLIENT_BANK_MARGIN
+    }
+
+    fn play(&self, rules: &CompiledRuleSet, rng: &mut ChaCha8Rng) -> Run {
+        let wheels = rules.wheel_count as usize;
+        let mut seed = vec![0u16; wheels];
+        let mut symbols = vec![Fruit::all()[0]; wheels];
+        let jackpot_bps = rules
+            .jackpot
+            .map(|j| j.contribution_bps as u64)
+            .unwrap_or(0);
+        let (min_exp, max_exp) = self.bet_exps;
+
+        let mut bank = self.bank;
+        let mut run = Run {
+            lowest_bank: bank,
+            ..Default::default()
+        };
+        for _ in 0..self.rounds {
+            // every player of the round saw the same balance
+            let offered = max_bet_exp(bank.saturating_sub(CLIENT_BANK_MARGIN));
+            for _ in 0..self.players {
+                let wanted = rng.gen_range(min_exp..=max_exp);
+                let exp = match offered {
+                    Some(offered) if offered >= min_exp => wanted.min(offered),
+                    _ => {
+                        run.refused += 1;
+                        continue;
+                    }
+                };
+                let bet = 1u64 << exp;
+                if bet >= bank {
+                    run.refused += 1;
+                    continue;
+                }
+                // the jackpot part of the bet goes to the pool, the pool pays the jackpot
+                bank += bet - bet / 10000 * jackpot_bps;
+
+                let mut free_spins = 0u32;
+                let mut free_spin = false;
+                loop {
+                    seed.iter_mut().for_each(|s| *s = rng.gen());
+                    let spin = rules.play_from_seed(&seed, &mut symbols, free_spin);
+                    free_spins += spin.free_spins as u32;
+                    let win = (bet as u128 * spin.reward as u128 / REWARD_ONE as u128) as u64;
+                    if win > 0 {
+                        let paid = win.min(max_payout(bank));
+                        run.wins += 1;
+                        run.owed += win as u128;
+                        if paid < win {
+                            run.capped += 1;
+                            run.short += (win - paid) as u128;
+                        }
+                        bank -= paid;
+                        run.lowest_bank = run.lowest_bank.min(bank);
+                    }
+                    if free_spins == 0 {
+                        break;
+                    }
+                    free_spins -= 1;
+                    free_spin = true;
+                }
+            }
+        }
+        run.final_bank = bank;
+        run
+    }
+}
+
+impl RuleSet {
+    /// Play `spec.runs` histories of the bank on every core.
+    pub fn simulate_solvency(&self, spec: &SolvencySpec) -> SolvencyReport {
+        assert!(spec.runs > 0 && spec.players > 0);
+        assert!(
+            spec.bet_exps.0 <= spec.bet_exps.1 && spec.bet_exps.1 < 57,
+            "bad bet exponents"
+        );
+        let (cdf, rewards) = (self.cdf_table(), self.reward_table());
+        let rules = self.compiled(&cdf, &rewards);
+
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(spec.runs as usize);
+        let next = AtomicUsize::new(0);
+        let done = Mutex::new(BTreeMap::new());
+        std::thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed) as u64;
+                    if index >= spec.runs {
+                        break;
+                    }
+                    let mut rng = ChaCha8Rng::seed_from_u64(spec.seed);
+                    rng.set_stream(index);
+                    let run = spec.play(&rules, &mut rng);
+                    done.lock().unwrap().insert(index, run);
+                });
+            }
+        });
+        let runs: Vec<Run> = done.into_inner().unwrap().into_values().collect();
+
+        let n = runs.len() as f64;
+        let share = |f: &dyn Fn(&Run) -> bool| runs.iter().filter(|r| f(r)).count() as f64 / n;
+        let full_service_bank = spec.full_service_bank();
+        let wins: u64 = runs.iter().map(|r| r.wins).sum();
+        let owed: u128 = runs.iter().map(|r| r.owed).sum();
+        let bets = spec.runs * spec.players as u64 * spec.rounds as u64;
+        SolvencyReport {
+            spec: spec.clone(),
+            below: spec
+                .thresholds
+                .iter()
+                .map(|t| (*t, share(&|r| r.lowest_bank < *t)))
+                .collect(),
+            full_service_bank,
+            degraded: share(&|r| r.capped > 0 || r.lowest_bank < full_service_bank),
+            capped_runs: share(&|r| r.capped > 0),
+            capped_rate: runs.iter().map(|r| r.capped).sum::<u64>() as f64 / wins.max(1) as f64,
+            short_share: runs.iter().map(|r| r.short).sum::<u128>() as f64 / owed.max(1) as f64,
+            refused_rate: runs.iter().map(|r| r.refused).sum::<u64>() as f64 / bets as f64,
+            mean_final_bank: runs.iter().map(|r| r.final_bank as f64).sum::<f64>() / n,
+            lowest_bank: runs.iter().map(|r| r.lowest_bank).min().unwrap(),
+        }
+    }
+
+    /// Smallest starting bank, to `SEARCH_PRECISION`, for which at most `max_risk` of
+    /// the runs cap a payout or stop offering the largest bet. `spec.bank` is ignored.
+    /// Every candidate plays the same random numbers, so the search is stable.
+    /// `None` when not even the largest bank a `u64` holds is safe enough.
+    pub fn recommend_bank(&self, spec: &SolvencySpec, max_risk: f64) -> Option<BankRecommendation> {
+        let try_bank = |bank: u64| {
+            self.simulate_solvency(&SolvencySpec {
+                bank,
+                ..spec.clone()
+            })
+        };
+        let mut low = spec.full_service_bank() - 1;
+        let mut high = spec.full_service_bank();
+        let mut report = try_bank(high);
+        while report.degraded > max_risk {
+            low = high;
+            high = high.checked_mul(2)?;
+            report = try_bank(high);
+        }
+        while (high - low) as f64 > high as f64 * SEARCH_PRECISION {
+            let mid = low + (high - low) / 2;
+            let mid_report = try_bank(mid);
+            if mid_report.degraded > max_risk {
+                low = mid;
+            } else {
+                high = mid;
+                report = mid_report;
+            }
+        }
+        Some(BankRecommendation {
+            max_risk,
+            bank: high,
+            report,
+        })
+    }
+}
+
+impl std::fmt::Display for SolvencyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let spec = &self.spec;
+        let sol = |lamports: f64| lamports / 1_000_000_000.0;
+        writeln!(
+            f,
+            "seed: {}  runs: {}  bank: {} SOL  players: {}  rounds: {}  bets: 2^{}..2^{} lamports",
+            spec.seed,
+            spec.runs,
+            sol(spec.bank as f64),
+            spec.players,
+            spec.rounds,
+            spec.bet_exps.0,
+            spec.bet_exps.1
+        )?;
+        writeln!(
+            f,
+            "mean final bank: {:.3} SOL  lowest bank: {:.3} SOL  largest bet offered from: {:.3} SOL",
+            sol(self.mean_final_bank),
+            sol(self.lowest_bank as f64),
+            sol(self.full_service_bank as f64)
+        )?;
+        writeln!(
+            f,
+            "capped payouts: {:.6} of wins, {:.6} of runs, {:.6} of the won lamports unpaid",
+            self.capped_rate, self.capped_runs, self.short_share
+        )?;
+        writeln!(
+            f,
+            "refused bets: {:.6}  degraded runs: {:.6}",
+            self.refused_rate, self.degraded
+        )?;
+        if !self.below.is_empty() {
+            writeln!(f, "| bank below SOL | runs |\n|---|---|")?;
+            for (threshold, share) in self.below.iter() {
+                writeln!(f, "| {} | {share:.6} |", sol(*threshold as f64))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for BankRecommendation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "recommended bank: {} SOL ({} lamports), at most {} of the runs degraded",
+            self.bank as f64 / 1_000_000_000.0,
+            self.bank,
+            self.max_risk
+        )?;
+        write!(f, "{}", self.report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bank::BANK_RESERVE;
+    use crate::rule_set::RewardShape;
+
+    fn spec() -> SolvencySpec {
+        SolvencySpec {
+            seed: 9,
+            runs: 64,
+            bank: 0,
+            players: 5,
+            rounds: 100,
+            bet_exps: (20, 22),
+            thresholds: vec![],
+        }
+    }
+
+    #[test]
+    fn test_solvency() {
+        let r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(12),
+            0.9,
+            3,
+            &[],
+            &RewardShape::default(),
+        );
+        let rich = SolvencySpec {
+            bank: 1 << 40,
+            thresholds: vec![1 << 40, 1 << 39],
+            ..spec()
+        };
+        let report = r.simulate_solvency(&rich);
+        assert_eq!(report, r.simulate_solvency(&rich));
+        assert_eq!(report.capped_runs, 0.0);
+        assert_eq!(report.refused_rate, 0.0);
+        assert_eq!(report.below[1].1, 0.0);
+        assert!(report.below[0].1 > 0.0);
+        assert!(report.mean_final_bank > rich.bank as f64);
+
+        // too small to pay the wins of the largest bets
+        let poor = SolvencySpec {
+            bank: spec().full_service_bank() + BANK_RESERVE,
+            ..spec()
+        };
+        let report = r.simulate_solvency(&poor);
+        assert!(report.capped_rate > 0.0);
+        assert!(report.short_share > 0.0);
+        assert!(report.degraded > 0.5);
+    }
+
+    #[test]
+    fn test_recommend_bank() {
+        let r = RuleSet::random_rule_set_from(
+            &mut ChaCha8Rng::seed_from_u64(13),
+            0.9,
+            3,
+            &[],
+            &RewardShape::default(),
+        );
+        let found = r.recommend_bank(&spec(), 0.05).unwrap();
+        assert!(found.report.degraded <= 0.05);
+        assert!(found.bank >= spec().full_service_bank());
+        let smaller = r.simulate_solvency(&SolvencySpec {
+            bank: found.bank * 9 / 10,
+            ..spec()
+        });
+        assert!(smaller.degraded > 0.0);
+
+        // no bank degrades in fewer than none of the runs
+        assert_eq!(r.recommend_bank(&spec(), -1.0), None);
+    }
+}
@@ -0,0 +1,73 @@
+//! Symbol metadata, declared per fruit in `src/fruits.toml`. build.rs generates
+//! the `Fruit` accessors (`display_name`, `tier`, `asset_path`, `colour`, `role`).
+
+/// How valuable a symbol looks, for the artwork and the pay table layout.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    strum_macros::EnumString,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum SymbolTier {
+    Low,
+    Mid,
+    High,
+    Special,
+}
+
+/// What a symbol is drawn for. Rule sets still pick their own wilds, scatter and jackpot.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    strum_macros::EnumString,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum SymbolRole {
+    Wild,
+    Scatter,
+    Jackpot,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Fruit;
+
+    #[test]
+    fn test_manifest_accessors() {
+        assert_eq!(Fruit::one_bar.to_link_str(), "one-bar");
+        assert_eq!(Fruit::one_bar.asset_path(), "img2/fruit/one-bar.png");
+        assert_eq!(Fruit::bar_bar_bar.display_name(), "Triple Bar");
+        assert_eq!(Fruit::seven.tier(), SymbolTier::High);
+        assert_eq!(Fruit::bonus.role(), Some(SymbolRole::Scatter));
+        assert_eq!(Fruit::seven.role(), None);
+        for fruit in Fruit::all() {
+            assert_eq!(fruit.colour().len(), 7);
+            assert_eq!(
+                fruit.role().is_some(),
+                fruit.tier() == SymbolTier::Special,
+                "{fruit}"
+            );
+        }
+    }
+}